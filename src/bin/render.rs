@@ -1,67 +1,976 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use base16cs::RenderOptions;
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use liquid::model::Value;
+use palette::Lab;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use base16cs::liquid::LiquidTemplate;
+use base16cs::liquid::{load_variables, LiquidTemplate};
+use base16cs::svg;
+use base16cs::Accents;
 use base16cs::Base16DerivedPalette;
+use base16cs::Base16OwnedDerivedPalette;
 use base16cs::Base16Palette;
+use base16cs::BaseColor;
+use base16cs::CssOptions;
+use base16cs::Deserializable;
+use base16cs::Format;
+use base16cs::GeneratorOptions;
+use base16cs::Interpolation;
 use base16cs::PaletteRenderer;
+use base16cs::PartialFiles;
+use base16cs::PartialOptions;
+use base16cs::PartialPrecedence;
+use base16cs::PartialsDir;
 use base16cs::Serializable;
+use base16cs::SwatchOptions;
+use base16cs::{ase, css, gpl};
 
 /// Load a Liquid template file and render it with the values of a colorscheme
 /// palette.
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// The path to the yaml file of the palette to load.
-    #[arg(short = 'p', long = "palette")]
-    palette: PathBuf,
-    /// The path(s) to directories for loading Liquid partials.
-    #[clap(short = 'd', long = "partial_dir", num_args = 0..)]
-    partials_dirs: Vec<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The path to the palette file to load, read by its extension (yaml, json,
+    /// toml, ron, gpl or ase), or `-` to read it from stdin (in `--format`).
+    /// Can be repeated to render several palettes (e.g. light and dark
+    /// variants) into one template, the first of which is its `palette`.
+    #[arg(short = 'p', long = "palette", required = true)]
+    palettes: Vec<PathBuf>,
+    #[command(flatten)]
+    partials: PartialArgs,
     /// Whether to unroll `color` objects into hex strings with their names as Liquid keys.
     #[arg(short = 'u', long = "unroll_colors_hex")]
     unroll_colors_hex: bool,
-    /// The path to the template file to read.
+    /// Fail on any undefined variable the template references (even in
+    /// conditions), reporting where and the closest defined names.
+    #[arg(long = "strict")]
+    strict_variables: bool,
+    /// The path to the template file to read, or `-` to read it from stdin.
     /// Without a template file, print the derived palette yaml and exit.
     #[arg(short = 't', long = "template")]
     template: Option<PathBuf>,
+    /// Override a palette color after loading it, as `name=#rrggbb` or
+    /// `name=l:L,a:A,b:B`, by its color name or Base16 role (e.g. `base0d`),
+    /// in every palette. Can be repeated.
+    #[arg(long = "set")]
+    overrides: Vec<String>,
+    /// An extra variable to inject into the template, as `name=value` (a
+    /// string value). Can be repeated.
+    #[arg(long = "var")]
+    vars: Vec<String>,
+    /// The path to a file (yaml, json, toml or ron) of extra variables to
+    /// inject into the template. Can be repeated; `--var` takes precedence.
+    #[arg(long = "vars")]
+    vars_files: Vec<PathBuf>,
+    /// The serialization format (yaml, json, toml or ron) of the derived
+    /// palette to print, and of the palette to load if its file extension is
+    /// not recognized.
+    #[arg(short = 'f', long = "format", default_value = "yaml")]
+    format: Format,
+    /// The path to the file to write the rendered template (or the derived
+    /// palette) to, replacing it atomically. Without it, print it.
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+    /// Instead of writing the output file, check that it is up to date: exit
+    /// with an error if its contents differ from the rendered ones.
+    #[arg(long = "check", requires = "output")]
+    check: bool,
+    /// Keep watching the palettes, the template and the partials directories,
+    /// and re-render whenever any of them changes.
+    #[cfg(feature = "watch")]
+    #[arg(
+        short = 'w',
+        long = "watch",
+        requires = "template",
+        conflicts_with = "check"
+    )]
+    watch: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print an SVG swatch sheet of a palette.
+    Svg(SvgArgs),
+    /// Write a PNG swatch sheet (or code sample preview) of a palette.
+    #[cfg(feature = "png")]
+    Png(PngArgs),
+    /// Export a palette for use in graphics applications.
+    Export(ExportArgs),
+    /// Render every palette into every template listed in a build manifest.
+    #[cfg(feature = "manifest")]
+    Build(BuildArgs),
+    /// List the color changes between two palettes.
+    Diff(DiffArgs),
+    /// Check that a derived palette's sRGB values match its L*a*b* values.
+    Verify(VerifyArgs),
+    /// Generate a palette from a background, a foreground and accent seeds.
+    Generate(GenerateArgs),
+    /// Derive the opposite-polarity (light or dark) variant of a palette.
+    Variant(VariantArgs),
+    /// Report the palette colors a template (and its partials) never
+    /// references, and the names it references that do not exist.
+    Lint(LintArgs),
+    /// Rewrite a palette file with all of its colors normalized to L*a*b* (and
+    /// its `extends`, if any, resolved).
+    Normalize(NormalizeArgs),
+}
+
+/// The arguments for loading Liquid partials, shared by the commands that
+/// parse templates.
+#[derive(Args)]
+struct PartialArgs {
+    /// The path(s) to directories for loading Liquid partials, each namespaced
+    /// by its name, or explicitly as `name=path`.
+    #[clap(short = 'd', long = "partial_dir", num_args = 0..)]
+    partials_dirs: Vec<PartialsDir>,
+    /// Which of several same-named partials (from different partials
+    /// directories) is used: the one from the first or the last directory.
+    #[arg(long = "partial_precedence", default_value = "last")]
+    partial_precedence: PartialPrecedence,
+    /// Fail on same-named partials in different partials directories, rather
+    /// than warn about them.
+    #[arg(long = "deny_partial_conflicts")]
+    deny_partial_conflicts: bool,
+}
+
+#[derive(Args)]
+struct SvgArgs {
+    /// The path to the palette file to draw.
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+    /// The path to the palette file of a second palette (e.g., the dark variant) to
+    /// draw side by side with the first.
+    #[arg(long = "pair")]
+    pair: Option<PathBuf>,
+    /// How many color tiles to lay out per row.
+    #[arg(short = 'c', long = "columns", default_value_t = 8)]
+    columns: usize,
+}
+
+#[cfg(feature = "png")]
+#[derive(Args)]
+struct PngArgs {
+    /// The path to the palette file to draw.
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+    /// The path to the PNG file to write.
+    #[arg(short = 'o', long = "output")]
+    output: PathBuf,
+    /// Draw a syntax-highlighted code sample instead of a swatch sheet.
+    #[arg(long = "code_sample")]
+    code_sample: bool,
+    /// How many color tiles to lay out per row.
+    #[arg(short = 'c', long = "columns", default_value_t = 8)]
+    columns: usize,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// The path to the palette file to export.
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+    /// The palette file format to export to.
+    #[arg(short = 'f', long = "format")]
+    format: ExportFormat,
+    /// The path to the file to write. Without it, write to stdout.
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+    /// (css) The path to the palette file of a dark palette to apply under
+    /// `prefers-color-scheme: dark`.
+    #[arg(long = "dark")]
+    dark: Option<PathBuf>,
+    /// (css) Whether to add `oklch()` values.
+    #[arg(long = "oklch")]
+    oklch: bool,
+    /// (css) Whether to add `color(display-p3 ...)` values.
+    #[arg(long = "display_p3")]
+    display_p3: bool,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// The path to the derived palette file to verify, read by its extension
+    /// (yaml, json, toml or ron).
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+}
+
+#[derive(Args)]
+struct LintArgs {
+    /// The path to the template file to lint.
+    #[arg(short = 't', long = "template")]
+    template: PathBuf,
+    /// The path to the palette file to lint the template against.
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+    #[command(flatten)]
+    partials: PartialArgs,
+    /// Whether the template is rendered with `--unroll_colors_hex`, making
+    /// color names variables.
+    #[arg(short = 'u', long = "unroll_colors_hex")]
+    unroll_colors_hex: bool,
+    /// An extra variable the template is rendered with, as `name=value`. Can
+    /// be repeated.
+    #[arg(long = "var")]
+    vars: Vec<String>,
+    /// The path to a file of extra variables the template is rendered with.
+    /// Can be repeated.
+    #[arg(long = "vars")]
+    vars_files: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+struct NormalizeArgs {
+    /// The path to the palette file to normalize.
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+    /// The path to the file to write, by its extension (yaml, json, toml or
+    /// ron). Without it, rewrite the palette file in place.
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// The name of the palette to generate.
+    #[arg(short = 'n', long = "name", default_value = "Generated")]
+    name: String,
+    /// The background color (base00), in hex.
+    #[arg(long = "bg")]
+    bg: String,
+    /// The default foreground color (base05), in hex.
+    #[arg(long = "fg")]
+    fg: String,
+    /// The 8 comma-separated accent colors (base08..base0f) in hex, of which
+    /// only the hues are kept.
+    #[arg(long = "accents", value_delimiter = ',', conflicts_with = "hues")]
+    accents: Vec<String>,
+    /// The 8 comma-separated accent hue angles (base08..base0f), in degrees.
+    /// Without them or accent colors, use the default Base16 accent hues.
+    #[arg(long = "hues", value_delimiter = ',')]
+    hues: Vec<f32>,
+    /// The accents' shared L* lightness.
+    #[arg(long = "lightness")]
+    lightness: Option<f32>,
+    /// The accents' shared (maximum) chroma.
+    #[arg(long = "chroma")]
+    chroma: Option<f32>,
+    /// Interpolate monotones in OKLab rather than in L*a*b*.
+    #[arg(long = "oklab")]
+    oklab: bool,
+    /// The path to the file to write, by its extension (yaml, json, toml or
+    /// ron). Without it, print the palette in YAML.
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct VariantArgs {
+    /// The path to the palette file to derive the variant of.
+    #[arg(short = 'p', long = "palette")]
+    palette: PathBuf,
+    /// The name of the variant. Without it, swap "light" and "dark" in the
+    /// palette's name.
+    #[arg(short = 'n', long = "name")]
+    name: Option<String>,
+    /// The path to the file to write, by its extension (yaml, json, toml or
+    /// ron). Without it, print the variant in YAML.
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[cfg(feature = "manifest")]
+#[derive(Args)]
+struct BuildArgs {
+    /// The path to the build manifest file.
+    #[arg(short = 'm', long = "manifest", default_value = "base16cs.toml")]
+    manifest: PathBuf,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// The path to the old palette file.
+    old: PathBuf,
+    /// The path to the new palette file.
+    new: PathBuf,
+    /// The serialization format (yaml, json, toml or ron) to print the
+    /// structured changes in. Without it, print a (markdown) report.
+    #[arg(short = 'f', long = "format")]
+    format: Option<Format>,
+}
+
+#[derive(Clone, ValueEnum)]
+enum ExportFormat {
+    /// GIMP (and Inkscape) palette.
+    Gpl,
+    /// Adobe Swatch Exchange, carrying canonical L*a*b* values.
+    Ase,
+    /// Krita palette.
+    #[cfg(feature = "kpl")]
+    Kpl,
+    /// CSS custom properties.
+    Css,
+    /// W3C Design Tokens Community Group JSON.
+    #[cfg(feature = "dtcg")]
+    Dtcg,
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
 
-    let palette_yaml = std::fs::read_to_string(&args.palette)?;
-    let palette = Base16Palette::from_yaml(&palette_yaml)?;
+    if let Some(command) = args.command {
+        return match command {
+            Command::Svg(svg_args) => print_svg(svg_args).map(|svg| print!("{}", svg)),
+            #[cfg(feature = "png")]
+            Command::Png(png_args) => write_png(png_args),
+            Command::Export(export_args) => write_export(export_args),
+            #[cfg(feature = "manifest")]
+            Command::Build(build_args) => build(build_args),
+            Command::Diff(diff_args) => print_diff(diff_args).map(|diff| print!("{}", diff)),
+            Command::Verify(verify_args) => verify(verify_args),
+            Command::Generate(generate_args) => generate(generate_args),
+            Command::Variant(variant_args) => variant(variant_args),
+            Command::Lint(lint_args) => lint(lint_args),
+            Command::Normalize(normalize_args) => normalize(normalize_args),
+        };
+    }
+
+    #[cfg(feature = "watch")]
+    if args.watch {
+        return watch(args);
+    }
+
+    let options = render_options(&args)?;
+    let overrides = parse_overrides(&args.overrides)?;
+    let stdin_inputs = args
+        .palettes
+        .iter()
+        .map(PathBuf::as_path)
+        .chain(args.template.as_deref())
+        .filter(|path| is_stdin(path))
+        .count();
+    if stdin_inputs > 1 {
+        bail!("Cannot read more than one of the palettes and the template from stdin");
+    }
+    let palettes = args
+        .palettes
+        .iter()
+        .map(|path| apply_overrides(load_palette_or(path, args.format)?, &overrides))
+        .collect::<Result<Vec<_>>>()?;
 
     let output = match args.template {
-        None => print_derived_palette(&palette),
-        Some(template_path) => render_template(
-            template_path,
-            args.partials_dirs,
-            &palette,
-            RenderOptions {
-                unroll_colors_hex: args.unroll_colors_hex,
-            },
-        ),
+        None => match palettes.as_slice() {
+            [palette] => print_derived_palette(palette, args.format),
+            _ => bail!("Cannot print more than one derived palette: render a template instead"),
+        },
+        Some(template_path) => {
+            let partials = args.partials.load()?;
+            render_template(template_path, &partials, &palettes, options)
+        }
     }?;
 
-    println!("{}", output);
+    if args.check {
+        // `output` is required by `--check`.
+        return check_output(&output, &args.output.unwrap());
+    }
+
+    write_output(&output, args.output.as_deref())
+}
+
+/// Builds the render options from the arguments.
+fn render_options(args: &Cli) -> Result<RenderOptions> {
+    Ok(RenderOptions {
+        unroll_colors_hex: args.unroll_colors_hex,
+        variables: variables(&args.vars_files, &args.vars)?,
+        strict_variables: args.strict_variables,
+    })
+}
+
+impl PartialArgs {
+    /// Loads the Liquid partials from the partials directories, warning about
+    /// any same-named ones.
+    fn load(&self) -> Result<PartialFiles> {
+        let options = PartialOptions {
+            precedence: self.partial_precedence,
+            deny_conflicts: self.deny_partial_conflicts,
+        };
+        let partials = PartialFiles::load(&self.partials_dirs, &options)?;
+        warn_partial_conflicts(&partials);
+
+        Ok(partials)
+    }
+}
+
+fn warn_partial_conflicts(partials: &PartialFiles) {
+    for conflict in partials.conflicts() {
+        eprintln!("Warning: {}", conflict);
+    }
+}
+
+/// Builds the extra template variables: `--vars` files are loaded in order,
+/// then `--var` pairs are set.
+fn variables(vars_files: &[PathBuf], vars: &[String]) -> Result<liquid::Object> {
+    let mut variables = liquid::Object::new();
+    for path in vars_files {
+        variables.extend(load_variables(path)?);
+    }
+    for var in vars {
+        let (name, value) = var
+            .split_once('=')
+            .with_context(|| format!("Invalid variable \"{}\": expected name=value", var))?;
+        variables.insert(name.to_string().into(), Value::scalar(value.to_string()));
+    }
+
+    Ok(variables)
+}
+
+/// Writes a rendered template (or derived palette) to the given path, or
+/// prints it, exactly as rendered.
+fn write_output(output: &str, path: Option<&Path>) -> Result<()> {
+    match path {
+        Some(path) => write_atomic(path, output.as_bytes())?,
+        None => print!("{}", output),
+    }
 
     Ok(())
 }
 
-fn print_derived_palette(palette: &Base16Palette) -> Result<String> {
+/// Writes the given bytes to a temporary file next to `path`, then renames it
+/// over `path`, so that readers never see a partially written file.
+///
+/// The permissions of the file being replaced, if any, are kept.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let write = || -> Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(bytes)?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    };
+
+    write().map_err(|err| {
+        let _ = std::fs::remove_file(&tmp_path);
+        err.context(format!("Could not write {}", path.display()))
+    })
+}
+
+/// Checks that the file at `path` has exactly the rendered contents, exiting
+/// with an error if it does not (or does not exist).
+fn check_output(output: &str, path: &Path) -> Result<()> {
+    let up_to_date = match std::fs::read(path) {
+        Ok(contents) => contents == output.as_bytes(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => false,
+        Err(err) => return Err(err).with_context(|| format!("Could not read {}", path.display())),
+    };
+    if up_to_date {
+        return Ok(());
+    }
+
+    eprintln!("{} is not up to date", path.display());
+    std::process::exit(exitcode::DATAERR);
+}
+
+/// Renders the template, then re-renders it whenever a palette, the template
+/// or any partial changes, until interrupted.
+///
+/// Bursts of filesystem events are debounced. Only what changed is reloaded:
+/// a palette, or the template (which also recompiles its partials). Errors
+/// are printed, and the next change retried, rather than exiting.
+#[cfg(feature = "watch")]
+fn watch(args: Cli) -> Result<()> {
+    use notify_debouncer_mini::new_debouncer;
+    use notify_debouncer_mini::notify::RecursiveMode;
+    use std::collections::HashSet;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    let options = render_options(&args)?;
+    let overrides = parse_overrides(&args.overrides)?;
+    // `template` is required by `--watch`.
+    let template_path = args.template.unwrap();
+    if is_stdin(&template_path) || args.palettes.iter().any(|path| is_stdin(path)) {
+        bail!("Cannot watch stdin for changes");
+    }
+    let palette_paths = args
+        .palettes
+        .iter()
+        .map(|path| canonical_file_path(path))
+        .collect::<Result<Vec<_>>>()?;
+    let template_path = canonical_file_path(&template_path)?;
+    let partials_dirs = args
+        .partials
+        .partials_dirs
+        .iter()
+        .map(|dir| {
+            dir.path.canonicalize().with_context(|| {
+                format!("Could not find partials directory {}", dir.path.display())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (tx, rx) = channel();
+    let mut debouncer = new_debouncer(Duration::from_millis(100), tx)?;
+    for dir in &partials_dirs {
+        debouncer.watcher().watch(dir, RecursiveMode::Recursive)?;
+    }
+    // Editors often save by replacing files, which would end a watch on the
+    // file itself: watch the directories they are in instead (including, as
+    // they are loaded, those of the palettes' `extends` chains).
+    let mut watched_dirs = HashSet::new();
+    let mut watch_dirs_of = |paths: &[PathBuf]| -> Result<()> {
+        for path in paths {
+            let dir = path.parent().unwrap_or(Path::new("/"));
+            if watched_dirs.insert(dir.to_path_buf()) {
+                debouncer
+                    .watcher()
+                    .watch(dir, RecursiveMode::NonRecursive)?;
+            }
+        }
+        Ok(())
+    };
+    watch_dirs_of(&palette_paths)?;
+    watch_dirs_of(std::slice::from_ref(&template_path))?;
+
+    let mut palettes: Vec<Option<Base16Palette>> = vec![None; palette_paths.len()];
+    // The files each palette was read from, including its `extends` chain.
+    let mut palettes_files: Vec<Vec<PathBuf>> = vec![Vec::new(); palette_paths.len()];
+    let mut template = None;
+    let mut palettes_changed = vec![true; palette_paths.len()];
+    let mut template_changed = true;
+    loop {
+        for (index, path) in palette_paths.iter().enumerate() {
+            if palettes_changed[index] {
+                let loaded = ok_or_print(load_palette_with_files(path, args.format));
+                palettes[index] = loaded.and_then(|(palette, files)| {
+                    // Keep the files of the last successful load on errors, to
+                    // retry on their next change.
+                    palettes_files[index] = files;
+                    ok_or_print(watch_dirs_of(&palettes_files[index]));
+                    ok_or_print(apply_overrides(palette, &overrides))
+                });
+            }
+        }
+        if template_changed {
+            template =
+                ok_or_print(args.partials.load().and_then(|partials| {
+                    LiquidTemplate::parse_file_with(&template_path, &partials)
+                }));
+        }
+        if template_changed || palettes_changed.contains(&true) {
+            let loaded: Option<Vec<&Base16Palette>> = palettes.iter().map(Option::as_ref).collect();
+            if let (Some(palettes), Some(template)) = (loaded, &template) {
+                let rendered = template.render_palettes(&palettes, options.clone());
+                if let Some(rendered) = ok_or_print(rendered) {
+                    ok_or_print(write_output(&rendered, args.output.as_deref()));
+                }
+            }
+        }
+
+        palettes_changed.fill(false);
+        template_changed = false;
+        let events = match rx.recv()? {
+            Ok(events) => events,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                continue;
+            }
+        };
+        for event in events {
+            for (index, files) in palettes_files.iter().enumerate() {
+                if palette_paths[index] == event.path || files.contains(&event.path) {
+                    palettes_changed[index] = true;
+                }
+            }
+            if event.path == template_path
+                || partials_dirs.iter().any(|dir| event.path.starts_with(dir))
+            {
+                template_changed = true;
+            }
+        }
+    }
+}
+
+/// Loads a palette, as [`load_palette_or`] does, also returning the canonical
+/// paths of the files of its `extends` chain, if any.
+#[cfg(feature = "watch")]
+fn load_palette_with_files(path: &Path, fallback: Format) -> Result<(Base16Palette, Vec<PathBuf>)> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gpl") | Some("ase") => Ok((load_palette_or(path, fallback)?, vec![])),
+        _ => Base16Palette::from_path_with_files(path, fallback),
+    }
+}
+
+/// Resolves a file's path into its canonical directory joined with its file
+/// name, which is how watch events report it.
+#[cfg(feature = "watch")]
+fn canonical_file_path(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("Could not find directory {}", dir.display()))?;
+
+    Ok(dir.join(file_name))
+}
+
+/// Prints an error (and its causes) instead of returning it.
+#[cfg(feature = "watch")]
+fn ok_or_print<T>(result: Result<T>) -> Option<T> {
+    result.map_err(|err| eprintln!("Error: {:#}", err)).ok()
+}
+
+/// Loads a palette, choosing its deserializer by the file's extension (YAML,
+/// unless otherwise recognized).
+fn load_palette(path: &Path) -> Result<Base16Palette> {
+    load_palette_or(path, Format::Yaml)
+}
+
+/// Loads a palette, choosing its deserializer by the file's extension, or by
+/// the given `fallback` format if the extension is not recognized. Palettes
+/// that `extends` another are resolved.
+fn load_palette_or(path: &Path, fallback: Format) -> Result<Base16Palette> {
+    if is_stdin(path) {
+        let s = std::io::read_to_string(std::io::stdin()).context("Could not read stdin")?;
+        return Base16Palette::from_str_in(&s, fallback, Path::new(""));
+    }
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gpl") => Base16Palette::from_gpl(&std::fs::read_to_string(path)?),
+        Some("ase") => Base16Palette::from_ase(&std::fs::read(path)?),
+        _ => Base16Palette::from_path(path, fallback),
+    }
+}
+
+/// Whether a path argument is `-`, i.e. stdin.
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+fn print_derived_palette(palette: &Base16Palette, format: Format) -> Result<String> {
     let derived_palette: Base16DerivedPalette = palette.into();
-    derived_palette.serialize()
+    derived_palette.serialize(format)
+}
+
+fn print_svg(args: SvgArgs) -> Result<String> {
+    let options = SwatchOptions {
+        columns: args.columns,
+        ..SwatchOptions::default()
+    };
+
+    let palette = load_palette(&args.palette)?;
+    let derived_palette: Base16DerivedPalette = (&palette).into();
+
+    match args.pair {
+        None => Ok(svg::swatch_sheet(&derived_palette, &options)),
+        Some(pair_path) => {
+            let pair = load_palette(&pair_path)?;
+            let derived_pair: Base16DerivedPalette = (&pair).into();
+            Ok(svg::swatch_sheet_pair(
+                &derived_palette,
+                &derived_pair,
+                &options,
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+fn write_png(args: PngArgs) -> Result<()> {
+    use base16cs::png;
+
+    let palette = load_palette(&args.palette)?;
+    let derived_palette: Base16DerivedPalette = (&palette).into();
+
+    let bytes = if args.code_sample {
+        png::code_sample(&derived_palette)
+    } else {
+        let options = SwatchOptions {
+            columns: args.columns,
+            ..SwatchOptions::default()
+        };
+        png::swatch_sheet(&derived_palette, &options)
+    }?;
+
+    std::fs::write(&args.output, bytes)?;
+
+    Ok(())
+}
+
+fn write_export(args: ExportArgs) -> Result<()> {
+    let palette = load_palette(&args.palette)?;
+    let derived_palette: Base16DerivedPalette = (&palette).into();
+
+    let bytes = match args.format {
+        ExportFormat::Gpl => gpl::to_string(&derived_palette).into_bytes(),
+        ExportFormat::Ase => ase::to_bytes(&derived_palette),
+        #[cfg(feature = "kpl")]
+        ExportFormat::Kpl => base16cs::kpl::to_bytes(&derived_palette)?,
+        ExportFormat::Css => {
+            let options = CssOptions {
+                oklch: args.oklch,
+                display_p3: args.display_p3,
+            };
+            match args.dark {
+                None => css::to_string(&derived_palette, &options),
+                Some(dark_path) => {
+                    let dark = load_palette(&dark_path)?;
+                    let derived_dark: Base16DerivedPalette = (&dark).into();
+                    css::to_string_light_dark(&derived_palette, &derived_dark, &options)
+                }
+            }
+            .into_bytes()
+        }
+        #[cfg(feature = "dtcg")]
+        ExportFormat::Dtcg => base16cs::dtcg::to_string(&derived_palette)?.into_bytes(),
+    };
+
+    match args.output {
+        Some(path) => std::fs::write(path, bytes)?,
+        None => std::io::stdout().write_all(&bytes)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "manifest")]
+fn build(args: BuildArgs) -> Result<()> {
+    let manifest = base16cs::Manifest::from_path(&args.manifest)?;
+    let partials = manifest.partials()?;
+    warn_partial_conflicts(&partials);
+    for path in manifest.build_with(&partials)? {
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+fn print_diff(args: DiffArgs) -> Result<String> {
+    let old = load_palette(&args.old)?;
+    let new = load_palette(&args.new)?;
+    let diff = old.diff(&new);
+
+    match args.format {
+        None => Ok(diff.to_string()),
+        Some(format) => diff.serialize(format),
+    }
+}
+
+fn verify(args: VerifyArgs) -> Result<()> {
+    let format = Format::from_path(&args.palette).unwrap_or(Format::Yaml);
+    let derived_palette =
+        Base16OwnedDerivedPalette::from_reader(File::open(&args.palette)?, format)?;
+
+    let mismatches = derived_palette.verify();
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+
+    for mismatch in &mismatches {
+        eprintln!("{}", mismatch);
+    }
+    std::process::exit(exitcode::DATAERR);
+}
+
+fn lint(args: LintArgs) -> Result<()> {
+    let palette = load_palette(&args.palette)?;
+    let partials = args.partials.load()?;
+    let template = LiquidTemplate::parse_file_with(&args.template, &partials)?;
+    let options = RenderOptions {
+        unroll_colors_hex: args.unroll_colors_hex,
+        variables: variables(&args.vars_files, &args.vars)?,
+        ..Default::default()
+    };
+
+    let report = template.lint(&palette, &options);
+    if report.is_clean() {
+        return Ok(());
+    }
+
+    for color in &report.unreferenced {
+        eprintln!("{}", color);
+    }
+    for reference in &report.unknown {
+        eprintln!("{}", reference);
+    }
+    std::process::exit(exitcode::DATAERR);
+}
+
+fn generate(args: GenerateArgs) -> Result<()> {
+    let accents = if !args.accents.is_empty() {
+        let colors = args
+            .accents
+            .iter()
+            .map(|hex| parse_hex(hex))
+            .collect::<Result<Vec<_>>>()?;
+        Accents::Colors(colors.try_into().map_err(|colors: Vec<_>| {
+            anyhow!("Expected 8 accent colors, found {}", colors.len())
+        })?)
+    } else if !args.hues.is_empty() {
+        Accents::Hues(
+            args.hues
+                .try_into()
+                .map_err(|hues: Vec<_>| anyhow!("Expected 8 accent hues, found {}", hues.len()))?,
+        )
+    } else {
+        Accents::default()
+    };
+
+    let options = GeneratorOptions {
+        name: args.name,
+        background: parse_hex(&args.bg)?,
+        foreground: parse_hex(&args.fg)?,
+        accents,
+        lightness: args.lightness,
+        chroma: args.chroma,
+        interpolation: if args.oklab {
+            Interpolation::Oklab
+        } else {
+            Interpolation::Lab
+        },
+    };
+    let palette = Base16Palette::generate(&options)?;
+
+    write_palette(&palette, args.output)
+}
+
+fn variant(args: VariantArgs) -> Result<()> {
+    let palette = load_palette(&args.palette)?;
+    let name = args.name.unwrap_or_else(|| opposite_name(&palette.name));
+
+    write_palette(&palette.opposite_variant(name), args.output)
+}
+
+/// Swaps "light" and "dark" (in lower or title case) in a palette's name.
+fn opposite_name(name: &str) -> String {
+    name.split(' ')
+        .map(|word| match word {
+            "light" => "dark",
+            "dark" => "light",
+            "Light" => "Dark",
+            "Dark" => "Light",
+            word => word,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes a base palette to the given path in the format of its extension,
+/// or prints it in YAML.
+fn write_palette(palette: &Base16Palette, output: Option<PathBuf>) -> Result<()> {
+    match output {
+        Some(path) => {
+            let format = Format::from_path(&path).with_context(|| {
+                format!(
+                    "Cannot write a palette to {}: unrecognized file extension",
+                    path.display()
+                )
+            })?;
+            std::fs::write(&path, palette.serialize(format)?)?;
+        }
+        None => print!("{}", palette.serialize(Format::Yaml)?),
+    }
+
+    Ok(())
+}
+
+/// Parses `--set` color overrides, as `name=#rrggbb` or `name=l:L,a:A,b:B`.
+fn parse_overrides(overrides: &[String]) -> Result<Vec<(String, Lab)>> {
+    overrides
+        .iter()
+        .map(|set| {
+            let (name, color) = set
+                .split_once('=')
+                .with_context(|| format!("Invalid override \"{}\": expected name=color", set))?;
+            let lab = if color.contains(':') {
+                parse_lab(color)?
+            } else {
+                parse_hex(color)?
+            };
+            Ok((name.to_string(), lab))
+        })
+        .collect()
+}
+
+/// Overrides a palette's colors, in order.
+fn apply_overrides(palette: Base16Palette, overrides: &[(String, Lab)]) -> Result<Base16Palette> {
+    overrides.iter().try_fold(palette, |palette, (name, lab)| {
+        palette.with_override(name, *lab)
+    })
+}
+
+/// Parses CIE L*a*b* values given as "l:L,a:A,b:B" (e.g. "l:46,a:0,b:-55").
+fn parse_lab(s: &str) -> Result<Lab> {
+    let (mut l, mut a, mut b) = (None, None, None);
+    for component in s.split(',') {
+        let (key, value) = component
+            .split_once(':')
+            .with_context(|| format!("Invalid L*a*b* component \"{}\" in \"{}\"", component, s))?;
+        let value: f32 = value
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid L*a*b* value \"{}\" in \"{}\"", value, s))?;
+        match key.trim() {
+            "l" => l = Some(value),
+            "a" => a = Some(value),
+            "b" => b = Some(value),
+            key => bail!("Unknown L*a*b* component \"{}\" in \"{}\"", key, s),
+        }
+    }
+
+    match (l, a, b) {
+        (Some(l), Some(a), Some(b)) => Ok(Lab::new(l, a, b)),
+        _ => bail!("Expected all of l, a and b in \"{}\"", s),
+    }
+}
+
+/// Parses a hex color (e.g. "#fef3da") into its CIE L*a*b* values.
+fn parse_hex(hex: &str) -> Result<Lab> {
+    let srgb = hex
+        .parse()
+        .map_err(|err| anyhow!("Invalid hex color \"{}\": {}", hex, err))?;
+    Ok(BaseColor::from_srgb("", srgb).lab)
+}
+
+fn normalize(args: NormalizeArgs) -> Result<()> {
+    let palette = load_palette(&args.palette)?;
+
+    let output = args.output.unwrap_or(args.palette);
+    write_palette(&palette, Some(output))
 }
 
 fn render_template(
     path: PathBuf,
-    partials_dirs: Vec<PathBuf>,
-    palette: &Base16Palette,
+    partials: &PartialFiles,
+    palettes: &[Base16Palette],
     render_options: RenderOptions,
 ) -> Result<String> {
-    let template = LiquidTemplate::parse_file(&path, partials_dirs)?;
-    template.render(palette, render_options)
+    let template = if is_stdin(&path) {
+        let source = std::io::read_to_string(std::io::stdin()).context("Could not read stdin")?;
+        LiquidTemplate::parse_str_with(&source, "<stdin>", partials)?
+    } else {
+        LiquidTemplate::parse_file_with(&path, partials)?
+    };
+    let palettes: Vec<&Base16Palette> = palettes.iter().collect();
+    template.render_palettes(&palettes, render_options)
 }
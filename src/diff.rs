@@ -0,0 +1,402 @@
+use palette::color_difference::Ciede2000;
+use serde::Serialize;
+use std::fmt;
+
+use crate::palette::{base16_role, contrast_ratio, BaseColor, DerivedColor, Palette};
+
+/// The WCAG 2 contrast ratio thresholds (against the background, base00) that
+/// are checked for flips: large text AA, AA and AAA.
+pub const CONTRAST_THRESHOLDS: [f32; 3] = [3.0, 4.5, 7.0];
+
+/// The structured differences between two palettes.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PaletteDiff {
+    /// The old and new names of the palette, if it was renamed.
+    pub renamed: Option<(String, String)>,
+
+    /// The color changes, in the new palette's order (removed colors last).
+    pub changes: Vec<ColorChange>,
+}
+
+/// A change to a single color of a palette.
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorChange {
+    /// A color which is only in the new palette.
+    Added { index: usize, color: BaseColor },
+
+    /// A color which is only in the old palette.
+    Removed { index: usize, color: BaseColor },
+
+    /// A color which is in both palettes, but was renamed, moved, changed
+    /// values, or flipped contrast thresholds.
+    Changed(ColorDiff),
+}
+
+/// The differences of a color between two palettes.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct ColorDiff {
+    /// The color's index in the old palette.
+    pub old_index: usize,
+
+    /// The color's index in the new palette.
+    pub new_index: usize,
+
+    /// The color in the old palette.
+    pub old: BaseColor,
+
+    /// The color in the new palette.
+    pub new: BaseColor,
+
+    /// The new color's L*, a* and b* values minus the old color's.
+    pub delta_lab: [f32; 3],
+
+    /// The CIEDE2000 color difference between the old and new colors.
+    pub delta_e: f32,
+
+    /// The old color's derived sRGB hex.
+    pub old_hex: String,
+
+    /// The new color's derived sRGB hex.
+    pub new_hex: String,
+
+    /// The contrast thresholds (against the background) that the color
+    /// passed in one palette, but not in the other.
+    pub contrast_flips: Vec<ContrastFlip>,
+}
+
+impl ColorDiff {
+    /// Whether the color was renamed.
+    pub fn is_renamed(&self) -> bool {
+        self.old.name != self.new.name
+    }
+}
+
+/// A WCAG 2 contrast ratio threshold that a color passed (against its
+/// palette's background) before a change but not after, or vice versa.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct ContrastFlip {
+    /// The contrast ratio threshold, one of `CONTRAST_THRESHOLDS`.
+    pub threshold: f32,
+
+    /// The old contrast ratio against the old background.
+    pub old_ratio: f32,
+
+    /// The new contrast ratio against the new background.
+    pub new_ratio: f32,
+}
+
+impl ContrastFlip {
+    /// Whether the color now passes the threshold.
+    pub fn passes(&self) -> bool {
+        self.new_ratio >= self.threshold
+    }
+}
+
+impl<const N: usize> Palette<N> {
+    /// Lists the changes from this (old) palette to another (new) one.
+    ///
+    /// Colors are matched by name first, and then, among the remaining ones,
+    /// by index (as renamed colors). Colors left unmatched were removed from
+    /// this palette, or added to the other.
+    pub fn diff(&self, other: &Palette<N>) -> PaletteDiff {
+        let mut matches: [Option<usize>; N] = [None; N];
+        let mut matched = [false; N];
+
+        // Match by name, preferring the same index when names are duplicated.
+        for (new_index, color) in other.colors.iter().enumerate() {
+            let candidates =
+                || (0..N).filter(|&index| !matched[index] && self.colors[index].name == color.name);
+            let old_index = candidates()
+                .find(|&index| index == new_index)
+                .or_else(|| candidates().next());
+            if let Some(old_index) = old_index {
+                matches[new_index] = Some(old_index);
+                matched[old_index] = true;
+            }
+        }
+        // Match the rest by index, as renamed colors.
+        for new_index in 0..N {
+            if matches[new_index].is_none() && !matched[new_index] {
+                matches[new_index] = Some(new_index);
+                matched[new_index] = true;
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (new_index, old_index) in matches.iter().enumerate() {
+            let new = &other.colors[new_index];
+            match old_index {
+                None => changes.push(ColorChange::Added {
+                    index: new_index,
+                    color: new.clone(),
+                }),
+                Some(old_index) => {
+                    let diff = color_diff(self, other, *old_index, new_index);
+                    if diff.is_renamed()
+                        || diff.old_index != diff.new_index
+                        || diff.old.lab != diff.new.lab
+                        || !diff.contrast_flips.is_empty()
+                    {
+                        changes.push(ColorChange::Changed(diff));
+                    }
+                }
+            }
+        }
+        for (index, color) in self.colors.iter().enumerate() {
+            if !matched[index] {
+                changes.push(ColorChange::Removed {
+                    index,
+                    color: color.clone(),
+                });
+            }
+        }
+
+        PaletteDiff {
+            renamed: (self.name != other.name).then(|| (self.name.clone(), other.name.clone())),
+            changes,
+        }
+    }
+}
+
+/// Compares a color between an old and a new palette.
+fn color_diff<const N: usize>(
+    old_palette: &Palette<N>,
+    new_palette: &Palette<N>,
+    old_index: usize,
+    new_index: usize,
+) -> ColorDiff {
+    let old = &old_palette.colors[old_index];
+    let new = &new_palette.colors[new_index];
+
+    // The background's contrast against itself is meaningless.
+    let contrast_flips = if old_index == 0 || new_index == 0 {
+        Vec::new()
+    } else {
+        let old_ratio = contrast_ratio(&old.lab, &old_palette.colors[0].lab);
+        let new_ratio = contrast_ratio(&new.lab, &new_palette.colors[0].lab);
+        CONTRAST_THRESHOLDS
+            .iter()
+            .filter(|&&threshold| (old_ratio >= threshold) != (new_ratio >= threshold))
+            .map(|&threshold| ContrastFlip {
+                threshold,
+                old_ratio,
+                new_ratio,
+            })
+            .collect()
+    };
+
+    ColorDiff {
+        old_index,
+        new_index,
+        old: old.clone(),
+        new: new.clone(),
+        delta_lab: [
+            new.lab.l - old.lab.l,
+            new.lab.a - old.lab.a,
+            new.lab.b - old.lab.b,
+        ],
+        delta_e: old.lab.difference(new.lab),
+        old_hex: DerivedColor::from(old).srgb_hex,
+        new_hex: DerivedColor::from(new).srgb_hex,
+        contrast_flips,
+    }
+}
+
+impl fmt::Display for PaletteDiff {
+    /// Formats this diff as a human-readable (markdown) report, one color
+    /// change per list item.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((old, new)) = &self.renamed {
+            writeln!(f, "Renamed palette: {} -> {}", old, new)?;
+        }
+        if self.changes.is_empty() {
+            return writeln!(f, "No color changes");
+        }
+
+        for change in &self.changes {
+            match change {
+                ColorChange::Added { index, color } => writeln!(
+                    f,
+                    "- Added {} {}: {} #{}",
+                    base16_role(*index),
+                    color.name,
+                    lab(color),
+                    DerivedColor::from(color).srgb_hex
+                )?,
+                ColorChange::Removed { index, color } => writeln!(
+                    f,
+                    "- Removed {} {}: {} #{}",
+                    base16_role(*index),
+                    color.name,
+                    lab(color),
+                    DerivedColor::from(color).srgb_hex
+                )?,
+                ColorChange::Changed(diff) => {
+                    write!(
+                        f,
+                        "- Changed {} {}",
+                        base16_role(diff.new_index),
+                        diff.new.name
+                    )?;
+                    if diff.is_renamed() {
+                        write!(f, " (renamed from {})", diff.old.name)?;
+                    }
+                    if diff.old_index != diff.new_index {
+                        write!(f, " (moved from {})", base16_role(diff.old_index))?;
+                    }
+                    writeln!(f)?;
+
+                    if diff.old.lab != diff.new.lab {
+                        let [l, a, b] = diff.delta_lab;
+                        writeln!(
+                            f,
+                            "  - {} -> {} (ΔL* {:+.2}, Δa* {:+.2}, Δb* {:+.2}, ΔE00 {:.2})",
+                            lab(&diff.old),
+                            lab(&diff.new),
+                            l,
+                            a,
+                            b,
+                            diff.delta_e
+                        )?;
+                        writeln!(f, "  - #{} -> #{}", diff.old_hex, diff.new_hex)?;
+                    }
+                    for flip in &diff.contrast_flips {
+                        writeln!(
+                            f,
+                            "  - Contrast {:.2} -> {:.2}: {} {}:1",
+                            flip.old_ratio,
+                            flip.new_ratio,
+                            if flip.passes() {
+                                "now passes"
+                            } else {
+                                "no longer passes"
+                            },
+                            flip.threshold
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a color's CIE L*a*b* values.
+fn lab(color: &BaseColor) -> String {
+    format!("lab({} {} {})", color.lab.l, color.lab.a, color.lab.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<3> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+                BaseColor::new("dim", 62, -4, 1),
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_diff_none(palette: Palette<3>) {
+        let diff = palette.diff(&palette);
+        assert_eq!(diff.renamed, None);
+        assert!(diff.changes.is_empty());
+        assert_eq!(diff.to_string(), "No color changes\n");
+    }
+
+    #[rstest]
+    fn test_diff_changed(palette: Palette<3>) {
+        let mut new = palette.clone();
+        new.name = "New Palette".to_string();
+        new.colors[2] = BaseColor::new("dim", 50, -4, 1);
+
+        let diff = palette.diff(&new);
+        assert_eq!(
+            diff.renamed,
+            Some(("My Palette".to_string(), "New Palette".to_string()))
+        );
+        assert_eq!(diff.changes.len(), 1);
+
+        let ColorChange::Changed(change) = &diff.changes[0] else {
+            panic!("Expected a changed color: {:?}", diff.changes[0]);
+        };
+        assert!(!change.is_renamed());
+        assert_eq!(change.delta_lab, [-12.0, 0.0, 0.0]);
+        assert!(change.delta_e > 10.0);
+        assert_eq!(change.old_hex, "8f9894");
+
+        // dim went from failing to passing large text AA.
+        assert_eq!(change.contrast_flips.len(), 1);
+        assert_eq!(change.contrast_flips[0].threshold, 3.0);
+        assert!(change.contrast_flips[0].passes());
+
+        let report = diff.to_string();
+        assert!(report
+            .starts_with("Renamed palette: My Palette -> New Palette\n- Changed base02 dim\n"));
+        assert!(report.contains(": now passes 3:1\n"));
+    }
+
+    #[rstest]
+    fn test_diff_renamed_added_removed(palette: Palette<3>) {
+        let new = Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("text", 31, -6, -6),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        );
+
+        let diff = palette.diff(&new);
+        assert_eq!(diff.changes.len(), 3);
+        assert_eq!(
+            diff.changes[0],
+            ColorChange::Added {
+                index: 1,
+                color: BaseColor::new("text", 31, -6, -6)
+            }
+        );
+
+        let ColorChange::Changed(moved) = &diff.changes[1] else {
+            panic!("Expected a changed color: {:?}", diff.changes[1]);
+        };
+        assert!(!moved.is_renamed());
+        assert_eq!((moved.old_index, moved.new_index), (1, 2));
+        assert_eq!(moved.delta_e, 0.0);
+
+        assert_eq!(
+            diff.changes[2],
+            ColorChange::Removed {
+                index: 2,
+                color: BaseColor::new("dim", 62, -4, 1)
+            }
+        );
+    }
+
+    #[rstest]
+    fn test_diff_renamed_in_place(palette: Palette<3>) {
+        let mut new = palette.clone();
+        new.colors[2].name = "comment".to_string();
+
+        let diff = palette.diff(&new);
+        assert_eq!(diff.changes.len(), 1);
+        let ColorChange::Changed(renamed) = &diff.changes[0] else {
+            panic!("Expected a changed color: {:?}", diff.changes[0]);
+        };
+        assert!(renamed.is_renamed());
+        assert_eq!(
+            diff.to_string(),
+            "- Changed base02 comment (renamed from dim)\n"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{bail, Context, Result};
+use palette::lab::Lab;
+use palette::rgb::Srgb;
+use palette::IntoColor;
+
+use crate::palette::{BaseColor, DerivedPalette, Palette};
+
+const ASE_SIGNATURE: &[u8; 4] = b"ASEF";
+const ASE_VERSION: (u16, u16) = (1, 0);
+
+const BLOCK_GROUP_START: u16 = 0xc001;
+const BLOCK_GROUP_END: u16 = 0xc002;
+const BLOCK_COLOR: u16 = 0x0001;
+
+/// The "normal" (i.e., neither global nor spot) color type.
+const COLOR_TYPE_NORMAL: u16 = 2;
+
+/// Serializes a derived palette into an Adobe Swatch Exchange file's contents.
+///
+/// Colors are written in the `LAB` color model straight from their canonical
+/// CIE L*a*b* values, rather than rounded through sRGB (see
+/// [`Palette::from_ase`] for how their L* is read back). They are grouped under
+/// the palette's name.
+pub fn to_bytes<const N: usize>(palette: &DerivedPalette<N>) -> Vec<u8> {
+    let mut ase = Vec::new();
+    ase.extend_from_slice(ASE_SIGNATURE);
+    ase.extend_from_slice(&ASE_VERSION.0.to_be_bytes());
+    ase.extend_from_slice(&ASE_VERSION.1.to_be_bytes());
+    ase.extend_from_slice(&(N as u32 + 2).to_be_bytes());
+
+    write_block(&mut ase, BLOCK_GROUP_START, &utf16_name(palette.name));
+    for color in palette.colors.iter() {
+        let lab = &color.base.lab;
+        let mut data = utf16_name(&color.base.name);
+        data.extend_from_slice(b"LAB ");
+        for value in [lab.l / 100.0, lab.a, lab.b] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&COLOR_TYPE_NORMAL.to_be_bytes());
+        write_block(&mut ase, BLOCK_COLOR, &data);
+    }
+    write_block(&mut ase, BLOCK_GROUP_END, &[]);
+
+    ase
+}
+
+impl<const N: usize> Palette<N> {
+    /// Deserializes a base palette from an Adobe Swatch Exchange file's contents.
+    ///
+    /// Colors in the `LAB` model are taken as-is, except for their L*, which
+    /// ASE stores divided by 100: it is read back with the precision it was
+    /// written with. `RGB ` and `Gray` colors are converted to CIE L*a*b*. The
+    /// palette is named after the first group in the file, if any.
+    pub fn from_ase(ase: &[u8]) -> Result<Palette<N>> {
+        let mut reader = Reader { bytes: ase };
+        if reader.take(4)? != ASE_SIGNATURE {
+            bail!("Not an Adobe Swatch Exchange file: missing \"ASEF\" signature");
+        }
+        reader.take(4)?; // version
+        let block_count = reader.u32()?;
+
+        let mut name = None;
+        let mut colors = Vec::new();
+        for _ in 0..block_count {
+            let block_type = reader.u16()?;
+            let block_len = reader.u32()? as usize;
+            let mut block = Reader {
+                bytes: reader.take(block_len)?,
+            };
+
+            match block_type {
+                BLOCK_GROUP_START if name.is_none() => name = Some(block.utf16_name()?),
+                BLOCK_COLOR => {
+                    let color_name = block.utf16_name()?;
+                    let lab = block.color().with_context(|| {
+                        format!("Could not read swatch \"{}\" in ASE file", color_name)
+                    })?;
+                    colors.push(BaseColor {
+                        name: color_name,
+                        lab,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Palette::from_vec(name.unwrap_or_default(), colors)
+    }
+}
+
+fn write_block(ase: &mut Vec<u8>, block_type: u16, data: &[u8]) {
+    ase.extend_from_slice(&block_type.to_be_bytes());
+    ase.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    ase.extend_from_slice(data);
+}
+
+/// Encodes a name as its length-prefixed, null-terminated UTF-16BE form.
+fn utf16_name(name: &str) -> Vec<u8> {
+    let units: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut data = (units.len() as u16).to_be_bytes().to_vec();
+    for unit in units {
+        data.extend_from_slice(&unit.to_be_bytes());
+    }
+    data
+}
+
+/// Converts an ASE `LAB` lightness (L* divided by 100) back to L*.
+///
+/// Dividing by 100 maps some neighbouring L* values onto the same lightness, so
+/// of those that are written as it, the one with the shortest decimal
+/// representation is taken: L* values with up to 4 decimals round-trip exactly.
+fn lightness(value: f32) -> f32 {
+    let l = (f64::from(value) * 100.0) as f32;
+    let candidates = [
+        l.next_down().next_down(),
+        l.next_down(),
+        l,
+        l.next_up(),
+        l.next_up().next_up(),
+    ];
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate / 100.0 == value)
+        .min_by_key(|candidate| candidate.to_string().len())
+        .unwrap_or(l)
+}
+
+/// A cursor over big-endian ASE data.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            bail!("Unexpected end of ASE data");
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn utf16_name(&mut self) -> Result<String> {
+        let len = self.u16()? as usize;
+        let units = self
+            .take(2 * len)?
+            .chunks_exact(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .take_while(|unit| *unit != 0)
+            .collect::<Vec<_>>();
+        String::from_utf16(&units).context("Invalid UTF-16 name in ASE data")
+    }
+
+    /// Reads a color model and its values, converting them to CIE L*a*b*.
+    fn color(&mut self) -> Result<Lab> {
+        let model = self.take(4)?;
+        let lab = match model {
+            b"LAB " => Lab::new(lightness(self.f32()?), self.f32()?, self.f32()?),
+            b"RGB " => Srgb::new(self.f32()?, self.f32()?, self.f32()?).into_color(),
+            b"Gray" => {
+                let gray = self.f32()?;
+                Srgb::new(gray, gray, gray).into_color()
+            }
+            _ => bail!(
+                "Unsupported color model \"{}\"",
+                String::from_utf8_lossy(model)
+            ),
+        };
+        Ok(lab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31.3, -6.7, -6.1),
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_ase_roundtrip(palette: Palette<2>) -> Result<()> {
+        let ase = to_bytes(&DerivedPalette::from(&palette));
+        assert!(ase.starts_with(b"ASEF\x00\x01\x00\x00\x00\x00\x00\x04"));
+
+        let de_palette = Palette::<2>::from_ase(&ase)?;
+        assert_eq!(de_palette, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ase_roundtrip_lightness() -> Result<()> {
+        for l in (0..=10000).map(|l| l as f32 / 100.0) {
+            let palette = Palette::new("Grays", [BaseColor::new("gray", l, 0.0, 0.0)]);
+            let ase = to_bytes(&DerivedPalette::from(&palette));
+            assert_eq!(Palette::<1>::from_ase(&ase)?, palette, "L* = {}", l);
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ase_deserialize_rgb() -> Result<()> {
+        let mut ase = b"ASEF\x00\x01\x00\x00\x00\x00\x00\x01".to_vec();
+        let mut data = utf16_name("white");
+        data.extend_from_slice(b"RGB ");
+        for value in [1f32, 1.0, 1.0] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&COLOR_TYPE_NORMAL.to_be_bytes());
+        write_block(&mut ase, BLOCK_COLOR, &data);
+
+        let de_palette = Palette::<1>::from_ase(&ase)?;
+        assert_eq!(de_palette.name, "");
+        assert_eq!(de_palette.colors[0].name, "white");
+        assert_eq!(
+            DerivedPalette::from(&de_palette).colors[0].srgb_hex,
+            "ffffff"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ase_deserialize_errors(palette: Palette<2>) {
+        let ase = to_bytes(&DerivedPalette::from(&palette));
+        Palette::<2>::from_ase(b"GIMP").expect_err("Missing signature");
+        Palette::<2>::from_ase(&ase[..ase.len() - 8]).expect_err("Truncated data");
+        Palette::<3>::from_ase(&ase).expect_err("Too few colors");
+    }
+}
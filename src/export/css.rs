@@ -0,0 +1,253 @@
+use palette::{lab::Lab, FromColor, Oklch, Xyz};
+use std::fmt::Write;
+
+use crate::palette::{base16_role, DerivedColor, DerivedPalette};
+
+/// CSS export options.
+#[derive(Default)]
+pub struct CssOptions {
+    /// Whether to add `oklch()` values for browsers that support them.
+    pub oklch: bool,
+
+    /// Whether to add `color(display-p3 ...)` values for browsers that support them.
+    pub display_p3: bool,
+}
+
+/// Serializes a derived palette into CSS custom properties on `:root`, one per
+/// Base16 role (e.g. `--base0d: #0096f5;`).
+///
+/// The sRGB hex values always come first. Wide-gamut values, if enabled, are
+/// declared again inside `@supports` blocks, so browsers that understand them
+/// render colors that fall outside of sRGB without clipping.
+pub fn to_string<const N: usize>(palette: &DerivedPalette<N>, options: &CssOptions) -> String {
+    let mut css = format!("/* {} */\n", escape_comment(palette.name));
+    write_root(&mut css, palette, options, "");
+
+    css
+}
+
+/// Serializes a light and a dark derived palette into CSS custom properties,
+/// with the dark palette's values applied under `prefers-color-scheme: dark`.
+pub fn to_string_light_dark<const N: usize>(
+    light: &DerivedPalette<N>,
+    dark: &DerivedPalette<N>,
+    options: &CssOptions,
+) -> String {
+    let mut css = format!("/* {} */\n", escape_comment(light.name));
+    write_root(&mut css, light, options, "");
+
+    write!(
+        css,
+        "\n/* {} */\n@media (prefers-color-scheme: dark) {{\n",
+        escape_comment(dark.name)
+    )
+    .unwrap();
+    write_root(&mut css, dark, options, "  ");
+    css.push_str("}\n");
+
+    css
+}
+
+/// Writes the `:root` rule for the given palette, followed by its wide-gamut
+/// `@supports` overrides, each line prefixed with `indent`.
+fn write_root<const N: usize>(
+    css: &mut String,
+    palette: &DerivedPalette<N>,
+    options: &CssOptions,
+    indent: &str,
+) {
+    write_rule(css, indent, None, palette, |color| {
+        format!("#{}", color.srgb_hex)
+    });
+
+    if options.display_p3 {
+        css.push('\n');
+        write_rule(
+            css,
+            indent,
+            Some("color(display-p3 0 0 0)"),
+            palette,
+            |color| display_p3(&color.base.lab),
+        );
+    }
+    if options.oklch {
+        css.push('\n');
+        write_rule(css, indent, Some("oklch(0% 0 0)"), palette, |color| {
+            oklch(&color.base.lab)
+        });
+    }
+}
+
+/// Writes a `:root` rule declaring each color's custom property, optionally
+/// wrapped in an `@supports (color: ...)` block.
+fn write_rule<'a, const N: usize, F>(
+    css: &mut String,
+    indent: &str,
+    supports: Option<&str>,
+    palette: &DerivedPalette<'a, N>,
+    value: F,
+) where
+    F: Fn(&DerivedColor<'a>) -> String,
+{
+    let mut indent = indent.to_string();
+    if let Some(supports) = supports {
+        writeln!(css, "{}@supports (color: {}) {{", indent, supports).unwrap();
+        indent.push_str("  ");
+    }
+
+    writeln!(css, "{}:root {{", indent).unwrap();
+    for (index, color) in palette.colors.iter().enumerate() {
+        writeln!(
+            css,
+            "{}  --{}: {}; /* {} */",
+            indent,
+            base16_role(index),
+            value(color),
+            escape_comment(&color.base.name)
+        )
+        .unwrap();
+    }
+    writeln!(css, "{}}}", indent).unwrap();
+
+    if supports.is_some() {
+        indent.truncate(indent.len() - 2);
+        writeln!(css, "{}}}", indent).unwrap();
+    }
+}
+
+/// Breaks up any `*/` in the given text, so that it cannot end the CSS comment
+/// it is written into.
+fn escape_comment(text: &str) -> String {
+    text.replace("*/", "* /")
+}
+
+/// Formats a CIE L*a*b* color as a CSS `oklch()` value.
+fn oklch(lab: &Lab) -> String {
+    let oklch = Oklch::from_color(*lab);
+    // The hue of an achromatic color is meaningless noise.
+    let hue = if oklch.chroma < 5e-5 {
+        0.0
+    } else {
+        oklch.hue.into_positive_degrees()
+    };
+    format!(
+        "oklch({:.2}% {:.4} {:.2})",
+        oklch.l * 100.0,
+        oklch.chroma,
+        hue
+    )
+}
+
+/// Formats a CIE L*a*b* color as a CSS `color(display-p3 ...)` value.
+///
+/// Channels are not clamped, so colors outside of the Display P3 gamut are
+/// left for the browser to map.
+fn display_p3(lab: &Lab) -> String {
+    // XYZ (D65) to linear Display P3, per CSS Color Module Level 4.
+    const XYZ_TO_LINEAR_P3: [[f32; 3]; 3] = [
+        [2.493_497, -0.931_383_6, -0.402_710_8],
+        [-0.829_489, 1.762_664_1, 0.023_624_687],
+        [0.035_845_83, -0.076_172_39, 0.956_884_5],
+    ];
+
+    let xyz = Xyz::from_color(*lab);
+    let [r, g, b] = XYZ_TO_LINEAR_P3.map(|row| {
+        let linear = row[0] * xyz.x + row[1] * xyz.y + row[2] * xyz.z;
+        // Display P3 shares the sRGB transfer function.
+        if linear.abs() <= 0.003_130_8 {
+            12.92 * linear
+        } else {
+            linear.signum() * (1.055 * linear.abs().powf(1.0 / 2.4) - 0.055)
+        }
+    });
+
+    format!("color(display-p3 {:.3} {:.3} {:.3})", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{BaseColor, Palette};
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_css(palette: Palette<2>) {
+        let derived = DerivedPalette::from(&palette);
+        let css = to_string(&derived, &CssOptions::default());
+        assert_eq!(
+            css,
+            r#"/* My Palette */
+:root {
+  --base00: #fef3da; /* bg */
+  --base01: #384c52; /* fg */
+}
+"#
+        );
+    }
+
+    #[rstest]
+    fn test_css_escaped_comments(mut palette: Palette<2>) {
+        palette.name = "My */ Palette".to_string();
+        palette.colors[0].name = "bg */".to_string();
+        let derived = DerivedPalette::from(&palette);
+
+        let css = to_string(&derived, &CssOptions::default());
+        assert!(css.starts_with("/* My * / Palette */\n"));
+        assert!(css.contains("--base00: #fef3da; /* bg * / */\n"));
+
+        let css = to_string_light_dark(&derived, &derived, &CssOptions::default());
+        assert!(css.contains("\n/* My * / Palette */\n@media"));
+    }
+
+    #[rstest]
+    fn test_css_wide_gamut(palette: Palette<2>) {
+        let derived = DerivedPalette::from(&palette);
+        let options = CssOptions {
+            oklch: true,
+            display_p3: true,
+        };
+        let css = to_string(&derived, &options);
+        assert!(css.contains(
+            "@supports (color: color(display-p3 0 0 0)) {\n  :root {\n    --base00: color(display-p3 "
+        ));
+        assert!(css.contains("@supports (color: oklch(0% 0 0)) {\n  :root {\n    --base00: oklch("));
+    }
+
+    #[rstest]
+    fn test_css_light_dark(palette: Palette<2>) {
+        let derived = DerivedPalette::from(&palette);
+        let css = to_string_light_dark(&derived, &derived, &CssOptions::default());
+        assert!(css.ends_with(
+            r#"@media (prefers-color-scheme: dark) {
+  :root {
+    --base00: #fef3da; /* bg */
+    --base01: #384c52; /* fg */
+  }
+}
+"#
+        ));
+    }
+
+    #[rstest]
+    fn test_wide_gamut_values() {
+        let white = Lab::new(100.0, 0.0, 0.0);
+        assert_eq!(oklch(&white), "oklch(100.00% 0.0000 0.00)");
+        assert_eq!(display_p3(&white), "color(display-p3 1.000 1.000 1.000)");
+
+        // sRGB's red primary lies within Display P3.
+        let red = Lab::new(53.2408, 80.0925, 67.2032);
+        assert_eq!(display_p3(&red), "color(display-p3 0.918 0.200 0.139)");
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::palette::{base16_role, DerivedPalette};
+
+/// A W3C Design Tokens Community Group group of color tokens.
+#[derive(Serialize)]
+struct TokenGroup<'a> {
+    #[serde(rename = "$description")]
+    description: &'a str,
+
+    #[serde(flatten)]
+    tokens: BTreeMap<String, ColorToken<'a>>,
+}
+
+/// A single color token, keyed by its Base16 role in a `TokenGroup`.
+#[derive(Serialize)]
+struct ColorToken<'a> {
+    #[serde(rename = "$type")]
+    kind: &'static str,
+
+    #[serde(rename = "$value")]
+    value: ColorValue,
+
+    #[serde(rename = "$description")]
+    description: &'a str,
+}
+
+/// A color token's value, in the canonical CIE L*a*b* colorspace with an sRGB
+/// hex fallback.
+#[derive(Serialize)]
+struct ColorValue {
+    #[serde(rename = "colorSpace")]
+    color_space: &'static str,
+
+    components: [f32; 3],
+
+    hex: String,
+}
+
+/// Serializes a derived palette into a W3C Design Tokens Community Group
+/// (DTCG) JSON file's contents.
+///
+/// Each color becomes a `color` token keyed by its Base16 role (e.g. `base0d`)
+/// and described by its name. Token values carry the exact L*a*b* components,
+/// along with the derived sRGB hex for tools that only read that.
+pub fn to_string<const N: usize>(palette: &DerivedPalette<N>) -> Result<String> {
+    let tokens = palette
+        .colors
+        .iter()
+        .enumerate()
+        .map(|(index, color)| {
+            let lab = &color.base.lab;
+            let token = ColorToken {
+                kind: "color",
+                value: ColorValue {
+                    color_space: "lab",
+                    components: [lab.l, lab.a, lab.b],
+                    hex: format!("#{}", color.srgb_hex),
+                },
+                description: &color.base.name,
+            };
+            (base16_role(index), token)
+        })
+        .collect();
+
+    let group = TokenGroup {
+        description: palette.name,
+        tokens,
+    };
+
+    serde_json::to_string_pretty(&group)
+        .with_context(|| format!("Could not serialize design tokens:\n{:?}", palette))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{BaseColor, Palette};
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    const PALETTE_TOKENS: &str = r##"{
+  "$description": "My Palette",
+  "base00": {
+    "$type": "color",
+    "$value": {
+      "colorSpace": "lab",
+      "components": [
+        96.0,
+        0.0,
+        13.0
+      ],
+      "hex": "#fef3da"
+    },
+    "$description": "bg"
+  },
+  "base01": {
+    "$type": "color",
+    "$value": {
+      "colorSpace": "lab",
+      "components": [
+        31.0,
+        -6.0,
+        -6.0
+      ],
+      "hex": "#384c52"
+    },
+    "$description": "fg"
+  }
+}"##;
+
+    #[rstest]
+    fn test_dtcg_serialize(palette: Palette<2>) -> Result<()> {
+        let derived = DerivedPalette::from(&palette);
+        assert_eq!(to_string(&derived)?, PALETTE_TOKENS);
+
+        Ok(())
+    }
+}
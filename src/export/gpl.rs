@@ -0,0 +1,142 @@
+use anyhow::{anyhow, bail, Context, Result};
+use palette::rgb::Srgb;
+use std::fmt::Write;
+
+use crate::palette::{base16_role, BaseColor, DerivedPalette, Palette};
+
+const GPL_HEADER: &str = "GIMP Palette";
+
+/// Serializes a derived palette into a GIMP palette file's contents.
+///
+/// Colors are written as their derived sRGB values, laid out in rows of 8.
+pub fn to_string<const N: usize>(palette: &DerivedPalette<N>) -> String {
+    let mut gpl = format!("{}\nName: {}\nColumns: 8\n#\n", GPL_HEADER, palette.name);
+    for color in palette.colors.iter() {
+        writeln!(
+            gpl,
+            "{:3} {:3} {:3}\t{}",
+            color.srgb.red, color.srgb.green, color.srgb.blue, color.base.name
+        )
+        .unwrap();
+    }
+
+    gpl
+}
+
+impl<const N: usize> Palette<N> {
+    /// Deserializes a base palette from a GIMP palette file's contents.
+    ///
+    /// Each color's sRGB values are converted to CIE L*a*b*. Colors without a
+    /// name are named after their Base16 role (e.g. `base0d`).
+    pub fn from_gpl(gpl: &str) -> Result<Palette<N>> {
+        let mut lines = gpl.lines();
+        if lines.next().map(str::trim) != Some(GPL_HEADER) {
+            bail!("Not a GIMP palette: missing \"{}\" header", GPL_HEADER);
+        }
+
+        let mut name = String::new();
+        let mut colors = Vec::new();
+        for (number, line) in lines.enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+                continue;
+            }
+            if let Some(palette_name) = line.strip_prefix("Name:") {
+                name = palette_name.trim().to_string();
+                continue;
+            }
+
+            let color = parse_color_line(line, colors.len())
+                .with_context(|| format!("Could not parse GIMP palette line {}", number + 2))?;
+            colors.push(color);
+        }
+
+        Palette::from_vec(name, colors)
+    }
+}
+
+/// Parses a "R G B [name]" color line into a base color.
+fn parse_color_line(line: &str, index: usize) -> Result<BaseColor> {
+    let mut fields = line.split_whitespace();
+    let mut channel = || -> Result<u8> {
+        let field = fields
+            .next()
+            .ok_or_else(|| anyhow!("Missing color channel in: \"{}\"", line))?;
+        field
+            .parse()
+            .with_context(|| format!("Invalid color channel \"{}\"", field))
+    };
+    let srgb = Srgb::new(channel()?, channel()?, channel()?);
+
+    let name = fields.collect::<Vec<_>>().join(" ");
+    let name = if name.is_empty() {
+        base16_role(index)
+    } else {
+        name
+    };
+
+    Ok(BaseColor::from_srgb(name, srgb))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    const PALETTE_GPL: &str = "GIMP Palette
+Name: My Palette
+Columns: 8
+#
+254 243 218\tbg
+ 56  76  82\tfg
+";
+
+    #[rstest]
+    fn test_gpl_serialize(palette: Palette<2>) {
+        let derived = DerivedPalette::from(&palette);
+        assert_eq!(to_string(&derived), PALETTE_GPL);
+    }
+
+    #[rstest]
+    fn test_gpl_deserialize() -> Result<()> {
+        let de_palette = Palette::<2>::from_gpl(PALETTE_GPL)?;
+        assert_eq!(de_palette.name, "My Palette");
+        assert_eq!(de_palette.colors[1].name, "fg");
+
+        // Lab values are recovered up to sRGB rounding.
+        let derived = DerivedPalette::from(&de_palette);
+        assert_eq!(derived.colors[0].srgb_hex, "fef3da");
+        assert_eq!(derived.colors[1].srgb_hex, "384c52");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_gpl_deserialize_unnamed_colors() -> Result<()> {
+        let gpl = "GIMP Palette\n0 0 0\n255 255 255\n";
+        let de_palette = Palette::<2>::from_gpl(gpl)?;
+        assert_eq!(de_palette.colors[0].name, "base00");
+        assert_eq!(de_palette.colors[1].name, "base01");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_gpl_deserialize_errors() {
+        Palette::<2>::from_gpl("Not a palette\n").expect_err("Missing header");
+        Palette::<2>::from_gpl("GIMP Palette\n0 0\n").expect_err("Missing channel");
+        Palette::<2>::from_gpl("GIMP Palette\n0 0 0 black\n").expect_err("Too few colors");
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::export::escape_xml;
+use crate::palette::{base16_role, DerivedPalette};
+
+const KPL_MIMETYPE: &str = "krita/x-colorset";
+const KPL_COLUMNS: usize = 8;
+
+/// The sRGB profile Krita ships with and tags its 8-bit RGB swatches with.
+const KPL_SRGB_PROFILE: &str = "sRGB-elle-V2-srgbtrc.icc";
+
+/// Serializes a derived palette into a Krita palette (`.kpl`) file's contents.
+///
+/// A `.kpl` file is a zip archive holding a mimetype marker, the color set as
+/// XML, and its (empty) list of embedded profiles. Colors are written as their
+/// derived sRGB values, laid out in rows of 8.
+pub fn to_bytes<const N: usize>(palette: &DerivedPalette<N>) -> Result<Vec<u8>> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut kpl = ZipWriter::new(Cursor::new(Vec::new()));
+
+    // The mimetype must come first, uncompressed, for Krita to sniff the file.
+    let entries = [
+        ("mimetype", KPL_MIMETYPE.to_string()),
+        ("colorset.xml", colorset_xml(palette)),
+        ("profiles.xml", "<Profiles/>\n".to_string()),
+    ];
+    for (filename, contents) in entries {
+        kpl.start_file(filename, options)
+            .with_context(|| format!("Could not add \"{}\" to Krita palette", filename))?;
+        kpl.write_all(contents.as_bytes())?;
+    }
+
+    let cursor = kpl
+        .finish()
+        .context("Could not finish Krita palette archive")?;
+    Ok(cursor.into_inner())
+}
+
+fn colorset_xml<const N: usize>(palette: &DerivedPalette<N>) -> String {
+    let mut xml = format!(
+        "<Colorset version=\"1.0\" readonly=\"false\" name=\"{}\" comment=\"\" columns=\"{}\" rows=\"{}\">\n",
+        escape_xml(palette.name),
+        KPL_COLUMNS,
+        N.div_ceil(KPL_COLUMNS),
+    );
+
+    for (index, color) in palette.colors.iter().enumerate() {
+        let srgb = &color.srgb;
+        write!(
+            xml,
+            concat!(
+                " <ColorSetEntry name=\"{}\" id=\"{}\" spot=\"false\" bitdepth=\"U8\">\n",
+                "  <RGB r=\"{}\" g=\"{}\" b=\"{}\" space=\"{}\"/>\n",
+                "  <Position row=\"{}\" column=\"{}\"/>\n",
+                " </ColorSetEntry>\n",
+            ),
+            escape_xml(&color.base.name),
+            base16_role(index),
+            srgb.red as f32 / 255.0,
+            srgb.green as f32 / 255.0,
+            srgb.blue as f32 / 255.0,
+            KPL_SRGB_PROFILE,
+            index / KPL_COLUMNS,
+            index % KPL_COLUMNS,
+        )
+        .unwrap();
+    }
+    xml.push_str("</Colorset>\n");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{BaseColor, Palette};
+
+    use rstest::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_kpl_serialize(palette: Palette<2>) -> Result<()> {
+        let kpl = to_bytes(&DerivedPalette::from(&palette))?;
+        let mut archive = ZipArchive::new(Cursor::new(kpl))?;
+        assert_eq!(archive.by_index(0)?.name(), "mimetype");
+
+        let mut colorset = String::new();
+        archive
+            .by_name("colorset.xml")?
+            .read_to_string(&mut colorset)?;
+        assert!(colorset
+            .starts_with("<Colorset version=\"1.0\" readonly=\"false\" name=\"My Palette\""));
+        assert!(colorset.contains(
+            " <ColorSetEntry name=\"fg\" id=\"base01\" spot=\"false\" bitdepth=\"U8\">\n"
+        ));
+        assert!(colorset.contains("  <Position row=\"0\" column=\"1\"/>\n"));
+
+        Ok(())
+    }
+}
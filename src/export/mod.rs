@@ -0,0 +1,25 @@
+/// Escapes a string for use as XML character data or attribute values.
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub mod ase;
+pub mod css;
+pub mod gpl;
+
+#[cfg(feature = "dtcg")]
+pub mod dtcg;
+
+#[cfg(feature = "kpl")]
+pub mod kpl;
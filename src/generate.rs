@@ -0,0 +1,299 @@
+use anyhow::{bail, Result};
+use palette::convert::FromColorUnclamped;
+use palette::rgb::Srgb;
+use palette::{lab::Lab, FromColor, Lch};
+
+use crate::palette::{Base16Palette, BaseColor, Palette};
+use crate::ramp::{mix, Interpolation};
+
+/// The names given to generated monotones, [base00..base07].
+const MONOTONE_NAMES: [&str; 8] = [
+    "bg_0", "bg_1", "bg_2", "dim_0", "fg_0", "fg_1", "fg_2", "fg_3",
+];
+
+/// Where [base00..base05] lie on the ramp from the background to the
+/// foreground.
+const MONOTONE_STEPS: [f32; 6] = [0.0, 0.1, 0.25, 0.5, 0.8, 1.0];
+
+/// Where [base06..base07] lie on the ramp from the foreground to white (or
+/// black, for light palettes).
+const EXTRA_MONOTONE_STEPS: [f32; 2] = [0.4, 0.8];
+
+/// The names given to generated accents, [base08..base0f].
+const ACCENT_NAMES: [&str; 8] = [
+    "red", "orange", "yellow", "green", "cyan", "blue", "magenta", "brown",
+];
+
+/// Default CIE LCh hue angles (in degrees) for the Base16 accents, in order:
+/// red, orange, yellow, green, cyan, blue, magenta and brown.
+pub const BASE16_ACCENT_HUES: [f32; 8] = [30.0, 60.0, 90.0, 135.0, 200.0, 270.0, 330.0, 45.0];
+
+/// How much an sRGB channel may overshoot [0.0, 1.0] and still count as being
+/// within gamut, to absorb conversion rounding.
+const GAMUT_EPSILON: f32 = 1e-4;
+
+/// The seeds of a generated palette's accents.
+#[derive(Clone, Debug)]
+pub enum Accents {
+    /// Accent colors, of which only the hues are kept.
+    Colors([Lab; 8]),
+    /// CIE LCh hue angles, in degrees.
+    Hues([f32; 8]),
+}
+
+impl Default for Accents {
+    fn default() -> Self {
+        Accents::Hues(BASE16_ACCENT_HUES)
+    }
+}
+
+/// Palette generator options.
+#[derive(Clone, Debug)]
+pub struct GeneratorOptions {
+    /// The generated palette's name.
+    pub name: String,
+
+    /// The background color, base00.
+    pub background: Lab,
+
+    /// The default foreground color, base05.
+    pub foreground: Lab,
+
+    /// The accents' seeds, in Base16 order [base08..base0f].
+    pub accents: Accents,
+
+    /// The accents' shared CIE L* lightness. Defaults to the accent colors'
+    /// mean lightness, or to 60 with hue angles.
+    pub lightness: Option<f32>,
+
+    /// The accents' shared CIE LCh chroma (at most). Defaults to the accent
+    /// colors' mean chroma, or to 60 with hue angles.
+    pub chroma: Option<f32>,
+
+    /// The colorspace in which to interpolate monotones.
+    pub interpolation: Interpolation,
+}
+
+impl Palette<16> {
+    /// Generates a Base16 palette from a background, a foreground and accent
+    /// seeds.
+    ///
+    /// Monotones [base00..base07] are interpolated from the background to the
+    /// foreground (base05), then towards white or black. Accents
+    /// [base08..base0f] keep their seeds' hues, at a shared lightness and
+    /// chroma.
+    ///
+    /// All colors are checked to be within the sRGB gamut: the accents' shared
+    /// chroma is lowered until all of them fit, and out-of-gamut monotones are
+    /// an error.
+    pub fn generate(options: &GeneratorOptions) -> Result<Base16Palette> {
+        let monotones = monotones(options);
+        for (name, lab) in MONOTONE_NAMES.iter().zip(&monotones) {
+            if !in_gamut(lab) {
+                bail!(
+                    "Generated monotone {} is outside of the sRGB gamut: lab({} {} {})",
+                    name,
+                    lab.l,
+                    lab.a,
+                    lab.b
+                );
+            }
+        }
+
+        let accents = accents(options)?;
+
+        let colors: Vec<BaseColor> = MONOTONE_NAMES
+            .iter()
+            .zip(monotones)
+            .chain(ACCENT_NAMES.iter().zip(accents))
+            .map(|(name, lab)| BaseColor {
+                name: name.to_string(),
+                lab,
+            })
+            .collect();
+
+        Palette::from_vec(options.name.clone(), colors)
+    }
+}
+
+/// Interpolates the monotones [base00..base07].
+fn monotones(options: &GeneratorOptions) -> [Lab; 8] {
+    let bg = options.background;
+    let fg = options.foreground;
+    let extreme = if fg.l > bg.l {
+        Lab::new(100.0, 0.0, 0.0)
+    } else {
+        Lab::new(0.0, 0.0, 0.0)
+    };
+
+    let mut monotones = [bg; 8];
+    for (monotone, t) in monotones.iter_mut().zip(MONOTONE_STEPS) {
+        *monotone = mix(bg, fg, t, options.interpolation);
+    }
+    for (monotone, t) in monotones[6..].iter_mut().zip(EXTRA_MONOTONE_STEPS) {
+        *monotone = mix(fg, extreme, t, options.interpolation);
+    }
+
+    monotones
+}
+
+/// Places the accents [base08..base0f] at a shared lightness and chroma, the
+/// latter lowered as needed to keep all of them within the sRGB gamut.
+fn accents(options: &GeneratorOptions) -> Result<[Lab; 8]> {
+    let (hues, mean_lightness, mean_chroma) = match &options.accents {
+        Accents::Hues(hues) => (*hues, 60.0, 60.0),
+        Accents::Colors(colors) => {
+            let lchs = colors.map(Lch::from_color);
+            (
+                lchs.map(|lch| lch.hue.into_positive_degrees()),
+                lchs.iter().map(|lch| lch.l).sum::<f32>() / 8.0,
+                lchs.iter().map(|lch| lch.chroma).sum::<f32>() / 8.0,
+            )
+        }
+    };
+    let lightness = options.lightness.unwrap_or(mean_lightness);
+    let chroma = options.chroma.unwrap_or(mean_chroma);
+
+    if !(0.0..=100.0).contains(&lightness) {
+        bail!(
+            "Accent lightness must be within [0, 100], found {}",
+            lightness
+        );
+    }
+    if chroma < 0.0 {
+        bail!("Accent chroma must not be negative, found {}", chroma);
+    }
+
+    let chroma = hues
+        .iter()
+        .map(|&hue| max_chroma(lightness, hue, chroma))
+        .fold(chroma, f32::min);
+
+    Ok(hues.map(|hue| Lab::from_color_unclamped(Lch::new(lightness, chroma, hue))))
+}
+
+/// Returns the highest chroma, up to `chroma`, at which the given lightness and
+/// hue are within the sRGB gamut.
+fn max_chroma(lightness: f32, hue: f32, chroma: f32) -> f32 {
+    let fits = |chroma| in_gamut(&Lab::from_color_unclamped(Lch::new(lightness, chroma, hue)));
+    if fits(chroma) {
+        return chroma;
+    }
+
+    let (mut low, mut high) = (0.0, chroma);
+    while high - low > 0.01 {
+        let mid = (low + high) / 2.0;
+        if fits(mid) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
+
+/// Whether a CIE L*a*b* color is within the sRGB gamut.
+pub(crate) fn in_gamut(lab: &Lab) -> bool {
+    let srgb = Srgb::from_color_unclamped(*lab);
+    [srgb.red, srgb.green, srgb.blue]
+        .iter()
+        .all(|channel| (-GAMUT_EPSILON..=1.0 + GAMUT_EPSILON).contains(channel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[fixture]
+    fn options() -> GeneratorOptions {
+        GeneratorOptions {
+            name: "Generated".to_string(),
+            background: Lab::new(96.0, 0.0, 13.0),
+            foreground: Lab::new(31.0, -6.0, -6.0),
+            accents: Accents::default(),
+            lightness: Some(55.0),
+            chroma: Some(30.0),
+            interpolation: Interpolation::Lab,
+        }
+    }
+
+    #[rstest]
+    fn test_generate(options: GeneratorOptions) -> Result<()> {
+        let palette = Base16Palette::generate(&options)?;
+        assert_eq!(palette.name, "Generated");
+        assert_eq!(palette.colors[0].lab, options.background);
+        assert_eq!(palette.colors[5].lab, options.foreground);
+        assert_eq!(palette.colors[8].name, "red");
+
+        // Monotones get darker towards black, past the foreground.
+        let lightnesses = palette.colors[..8].iter().map(|color| color.lab.l);
+        assert!(lightnesses
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|pair| pair[0] > pair[1]));
+
+        for accent in &palette.colors[8..] {
+            let lch = Lch::from_color(accent.lab);
+            assert!((lch.l - 55.0).abs() < 1e-3);
+            assert!((lch.chroma - 30.0).abs() < 1e-3);
+        }
+        assert!(palette.colors.iter().all(|color| in_gamut(&color.lab)));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_generate_lowers_chroma_into_gamut(mut options: GeneratorOptions) -> Result<()> {
+        options.chroma = Some(150.0);
+
+        let palette = Base16Palette::generate(&options)?;
+        let chromas = palette.colors[8..]
+            .iter()
+            .map(|color| Lch::from_color(color.lab).chroma);
+        for chroma in chromas {
+            assert!(chroma < 150.0);
+            assert!((chroma - Lch::from_color(palette.colors[8].lab).chroma).abs() < 1e-3);
+        }
+        assert!(palette.colors.iter().all(|color| in_gamut(&color.lab)));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_generate_from_accent_colors(mut options: GeneratorOptions) -> Result<()> {
+        let red = Lab::new(46.0, 66.0, 42.0);
+        options.accents = Accents::Colors([red; 8]);
+        options.lightness = None;
+        options.chroma = None;
+
+        let palette = Base16Palette::generate(&options)?;
+        let accent = palette.colors[8].lab;
+        assert!((accent.l - red.l).abs() < 1e-3);
+        assert!((accent.a - red.a).abs() < 1e-3);
+        assert!((accent.b - red.b).abs() < 1e-3);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_generate_oklab(mut options: GeneratorOptions) -> Result<()> {
+        let lab = Base16Palette::generate(&options)?;
+        options.interpolation = Interpolation::Oklab;
+        let oklab = Base16Palette::generate(&options)?;
+
+        assert!((oklab.colors[0].lab.l - lab.colors[0].lab.l).abs() < 1e-3);
+        assert!((oklab.colors[5].lab.l - lab.colors[5].lab.l).abs() < 1e-3);
+        assert!((oklab.colors[3].lab.a - lab.colors[3].lab.a).abs() > 0.1);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_generate_invalid_lightness(mut options: GeneratorOptions) {
+        options.lightness = Some(120.0);
+        assert!(Base16Palette::generate(&options).is_err());
+    }
+}
@@ -37,11 +37,13 @@
 //!
 //! ### Serializing and deserializing a palette (YAML)
 //!
-//! This crate is compiled with YAML serde by default.
+//! This crate is compiled with YAML serde by default. Other formats (JSON, TOML
+//! and RON) can be enabled with their cargo features, and are all selected at
+//! runtime with a [`Format`].
 //!
 //! ```rust
 //! # use base16cs::{Palette, BaseColor, DerivedPalette};
-//! use base16cs::Serializable;
+//! use base16cs::{Format, Serializable};
 //!
 //! # let palette = Palette::new(
 //! #     "My Palette",
@@ -50,7 +52,7 @@
 //! #         BaseColor::new("fg", 31, -6, -6),
 //! #     ]);
 //! # let derived_palette = DerivedPalette::from(&palette);
-//! let serialized = derived_palette.serialize().unwrap();
+//! let serialized = derived_palette.serialize(Format::Yaml).unwrap();
 //! assert_eq!(serialized, r#"name: My Palette
 //! colors:
 //! - base:
@@ -83,7 +85,7 @@
 //!
 //! ```rust
 //! # use base16cs::{Palette, BaseColor};
-//! # use base16cs::Serializable;
+//! use base16cs::{Deserializable, Format};
 //! # let palette = Palette::new(
 //! #     "My Palette",
 //! #     [
@@ -105,30 +107,170 @@
 //!     b: -6.0
 //! "#;
 //!
+//! let de_palette = Palette::<2>::from_str(yaml_str, Format::Yaml).unwrap();
+//! assert_eq!(de_palette, palette);
+//!
+//! // Or, equivalently:
 //! let de_palette = Palette::<2>::from_yaml(yaml_str).unwrap();
 //! assert_eq!(de_palette, palette);
 //! ```
 //!
+//! A serialized derived palette can be read back as an [`OwnedDerivedPalette`],
+//! and verified against its embedded L\*a\*b\* values, e.g. to catch
+//! hand-edited or stale derived files.
+//!
+//! ```rust
+//! # use base16cs::{Palette, BaseColor, DerivedPalette};
+//! use base16cs::{Deserializable, Format, OwnedDerivedPalette, Serializable};
+//! # let palette = Palette::new(
+//! #     "My Palette",
+//! #     [
+//! #         BaseColor::new("bg", 96, 0, 13),
+//! #         BaseColor::new("fg", 31, -6, -6),
+//! #     ]);
+//!
+//! let serialized = DerivedPalette::from(&palette).serialize(Format::Yaml).unwrap();
+//! let stale = serialized.replace("fef3da", "ffffff");
+//!
+//! let de_derived = OwnedDerivedPalette::<2>::from_str(&stale, Format::Yaml).unwrap();
+//! assert_eq!(de_derived.to_palette(), palette);
+//!
+//! let mismatches = de_derived.verify();
+//! assert_eq!(mismatches.len(), 1);
+//! assert_eq!(mismatches[0].derived_hex, "fef3da");
+//! ```
+//!
+//! ### Serializing and deserializing a palette (JSON)
+//!
+//! With the `json` feature, palettes can also be read from and written to JSON.
+//!
+//! ```rust
+//! # #[cfg(feature = "json")]
+//! # {
+//! # use base16cs::{Palette, BaseColor, DerivedPalette};
+//! # let palette = Palette::new(
+//! #     "My Palette",
+//! #     [
+//! #         BaseColor::new("bg", 96, 0, 13),
+//! #         BaseColor::new("fg", 31, -6, -6),
+//! #     ]);
+//! let json = palette.to_json().unwrap();
+//! let de_palette = Palette::<2>::from_json(&json).unwrap();
+//! assert_eq!(de_palette, palette);
+//!
+//! let derived_json = DerivedPalette::from(&palette).to_json().unwrap();
+//! assert!(derived_json.contains(r#""srgb_hex": "fef3da""#));
+//! # }
+//! ```
+//!
 //! ### Injecting a palette into a template for render
 //!
 //! See: [`template`](template/mod.rs) module.
+//!
+//! ### Generating an SVG swatch sheet
+//!
+//! ```rust
+//! # use base16cs::{Palette, BaseColor, DerivedPalette};
+//! use base16cs::{svg, SwatchOptions};
+//!
+//! # let palette = Palette::new(
+//! #     "My Palette",
+//! #     [
+//! #         BaseColor::new("bg", 96, 0, 13),
+//! #         BaseColor::new("fg", 31, -6, -6),
+//! #     ]);
+//! let derived_palette = DerivedPalette::from(&palette);
+//! let sheet = svg::swatch_sheet(&derived_palette, &SwatchOptions::default());
+//! assert!(sheet.contains("#fef3da"));
+//! ```
 
+mod diff;
+mod export;
+mod generate;
+#[cfg(feature = "manifest")]
+mod manifest;
 mod palette;
+mod preview;
+mod ramp;
 mod serialize;
 mod template;
+#[cfg(test)]
+mod test_utils;
+mod variant;
+
+pub use diff::ColorChange;
+pub use diff::ColorDiff;
+pub use diff::ContrastFlip;
+pub use diff::PaletteDiff;
+pub use diff::CONTRAST_THRESHOLDS;
+
+pub use export::ase;
+pub use export::css;
+pub use export::css::CssOptions;
+#[cfg(feature = "dtcg")]
+pub use export::dtcg;
+pub use export::gpl;
+#[cfg(feature = "kpl")]
+pub use export::kpl;
+
+pub use generate::Accents;
+pub use generate::GeneratorOptions;
+pub use generate::BASE16_ACCENT_HUES;
+
+#[cfg(feature = "manifest")]
+pub use manifest::BuildOutput;
+#[cfg(feature = "manifest")]
+pub use manifest::Manifest;
+#[cfg(feature = "manifest")]
+pub use manifest::ManifestTemplate;
+
+pub use palette::base16_role;
+pub use palette::slug;
 
 pub use palette::Base16Colors;
 pub use palette::Base16DerivedColors;
 pub use palette::Base16DerivedPalette;
+pub use palette::Base16OwnedDerivedPalette;
 pub use palette::Base16Palette;
 pub use palette::BaseColor;
+pub use palette::DerivationMismatch;
 pub use palette::DerivedColor;
 pub use palette::DerivedPalette;
+pub use palette::OwnedDerivedColor;
+pub use palette::OwnedDerivedPalette;
 pub use palette::Palette;
 
+#[cfg(feature = "png")]
+pub use preview::png;
+pub use preview::svg;
+pub use preview::SwatchOptions;
+
+pub use ramp::Interpolation;
+
+#[cfg(feature = "json")]
+pub use serialize::json;
+#[cfg(feature = "ron")]
+pub use serialize::ron;
+#[cfg(feature = "toml")]
+pub use serialize::toml;
 pub use serialize::yaml;
+pub use serialize::Deserializable;
+pub use serialize::Format;
 pub use serialize::Serializable;
 
 pub use template::liquid;
+pub use template::FrontMatter;
+pub use template::LintReport;
 pub use template::PaletteRenderer;
+pub use template::PartialConflict;
+pub use template::PartialFiles;
+pub use template::PartialOptions;
+pub use template::PartialPrecedence;
+#[cfg(feature = "liquid")]
+pub use template::PartialsDir;
+pub use template::ReferenceKind;
 pub use template::RenderOptions;
+pub use template::UnknownReference;
+pub use template::UnreferencedColor;
+
+pub use variant::Polarity;
@@ -0,0 +1,430 @@
+use anyhow::{bail, Context, Result};
+use liquid::ParserBuilder;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::palette::{slug, Base16Palette};
+use crate::serialize::Format;
+use crate::template::liquid::LiquidTemplate;
+use crate::template::{
+    FrontMatter, PaletteRenderer, PartialFiles, PartialOptions, PartialPrecedence, PartialsDir,
+    RenderOptions,
+};
+
+/// A build manifest (e.g. `base16cs.toml`), which lists the palettes to render
+/// into each of its templates:
+///
+/// ```toml
+/// palettes = ["palettes/selenized_light.yaml", "palettes/selenized_dark.yaml"]
+/// partials_dirs = ["partials"]
+/// unroll_colors_hex = true
+///
+/// [[templates]]
+/// path = "templates/kitty.conf.liquid"
+/// output = "out/{{ palette.slug }}/kitty.conf"
+/// ```
+///
+/// Paths are relative to the manifest file. Output paths are Liquid templates
+/// themselves, rendered with `palette.name`, `palette.slug` (e.g.
+/// `selenized-light`) and `template.name` (the template's file name, without
+/// its `.liquid` extension).
+///
+/// A template's own [`FrontMatter`] can also declare its output path (which
+/// the manifest's overrides), its file mode, and the palette variant it
+/// applies to; palettes of the other polarity are skipped for that template.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The paths to the palette files to render, read by their extensions.
+    pub palettes: Vec<PathBuf>,
+
+    /// The templates to render each palette into.
+    pub templates: Vec<ManifestTemplate>,
+
+    /// The paths to directories for loading Liquid partials, each optionally
+    /// namespaced explicitly as `name=path` (see [`PartialsDir`]).
+    #[serde(default)]
+    pub partials_dirs: Vec<PartialsDir>,
+
+    /// Which of several same-named partials is used: the one from the `first`
+    /// or the `last` (default) directory listed.
+    #[serde(default)]
+    pub partial_precedence: PartialPrecedence,
+
+    /// Whether same-named partials in different directories are an error.
+    #[serde(default)]
+    pub deny_partial_conflicts: bool,
+
+    /// Whether to unroll `color` objects into hex strings with their names as
+    /// Liquid keys.
+    #[serde(default)]
+    pub unroll_colors_hex: bool,
+
+    /// Whether to fail on any undefined variable a template references (see
+    /// [`RenderOptions::strict_variables`]).
+    #[serde(default)]
+    pub strict_variables: bool,
+
+    /// The directory that relative paths are resolved against.
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// A template listed in a build manifest.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestTemplate {
+    /// The path to the Liquid template file.
+    pub path: PathBuf,
+
+    /// The output path pattern, a Liquid template. Defaults to the template's
+    /// front-matter `output`.
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// A file to write, rendered from a palette and a template.
+pub struct BuildOutput {
+    /// The path to write to.
+    pub path: PathBuf,
+
+    /// The rendered contents.
+    pub contents: String,
+
+    /// The (unix) permissions to set on the written file, if any.
+    pub mode: Option<u32>,
+}
+
+impl Manifest {
+    /// Loads a (TOML) build manifest file.
+    pub fn from_path(path: &Path) -> Result<Manifest> {
+        let s = fs::read_to_string(path)
+            .with_context(|| format!("Could not read manifest file {}", path.display()))?;
+        let mut manifest: Manifest = toml::from_str(&s)
+            .with_context(|| format!("Could not parse manifest file {}", path.display()))?;
+        manifest.root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+        Ok(manifest)
+    }
+
+    /// Loads the Liquid partials from the partials directories, with their
+    /// conflicts (see [`PartialFiles`]).
+    pub fn partials(&self) -> Result<PartialFiles> {
+        let partials_dirs: Vec<PartialsDir> = self
+            .partials_dirs
+            .iter()
+            .map(|dir| PartialsDir {
+                path: self.root.join(&dir.path),
+                ..dir.clone()
+            })
+            .collect();
+        let options = PartialOptions {
+            precedence: self.partial_precedence,
+            deny_conflicts: self.deny_partial_conflicts,
+        };
+
+        PartialFiles::load(&partials_dirs, &options)
+    }
+
+    /// Renders every palette into every template, without writing anything.
+    ///
+    /// Fails if any two renders would be written to the same path.
+    pub fn render(&self) -> Result<Vec<BuildOutput>> {
+        self.render_with(&self.partials()?)
+    }
+
+    /// Renders every palette into every template, as [`Manifest::render`]
+    /// does, with already loaded partials (see [`Manifest::partials`]).
+    pub fn render_with(&self, partials: &PartialFiles) -> Result<Vec<BuildOutput>> {
+        let palettes = self
+            .palettes
+            .iter()
+            .map(|path| Base16Palette::from_path(&self.root.join(path), Format::Yaml))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut outputs = Vec::new();
+        let mut sources: HashMap<PathBuf, (String, PathBuf)> = HashMap::new();
+        for template in &self.templates {
+            let template_path = self.root.join(&template.path);
+            let parsed = LiquidTemplate::parse_file_with(&template_path, partials)?;
+            let FrontMatter {
+                output,
+                mode,
+                variant,
+                ..
+            } = parsed.front_matter();
+            let Some(pattern) = template.output.as_ref().or(output.as_ref()) else {
+                bail!(
+                    "Template {} has no output path, in the manifest or its front-matter",
+                    template.path.display()
+                );
+            };
+
+            for palette in &palettes {
+                if variant.is_some_and(|variant| variant != palette.polarity()) {
+                    continue;
+                }
+
+                let path = self
+                    .root
+                    .join(output_path(pattern, palette, &template.path)?);
+                if let Some((other_palette, other_template)) =
+                    sources.insert(path.clone(), (palette.name.clone(), template.path.clone()))
+                {
+                    bail!(
+                        "Output path {} is rendered from both {} with {} and {} with {}",
+                        path.display(),
+                        other_template.display(),
+                        other_palette,
+                        template.path.display(),
+                        palette.name
+                    );
+                }
+
+                let contents = parsed.render(
+                    palette,
+                    RenderOptions {
+                        unroll_colors_hex: self.unroll_colors_hex,
+                        strict_variables: self.strict_variables,
+                        ..Default::default()
+                    },
+                )?;
+                outputs.push(BuildOutput {
+                    path,
+                    contents,
+                    mode: *mode,
+                });
+            }
+        }
+
+        Ok(outputs)
+    }
+
+    /// Renders every palette into every template, and writes them to their
+    /// output paths (creating directories as needed).
+    ///
+    /// Returns the paths written to.
+    pub fn build(&self) -> Result<Vec<PathBuf>> {
+        self.build_with(&self.partials()?)
+    }
+
+    /// Renders every palette into every template, and writes them, as
+    /// [`Manifest::build`] does, with already loaded partials (see
+    /// [`Manifest::partials`]).
+    pub fn build_with(&self, partials: &PartialFiles) -> Result<Vec<PathBuf>> {
+        let outputs = self.render_with(partials)?;
+
+        for output in &outputs {
+            if let Some(dir) = output.path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("Could not create directory {}", dir.display()))?;
+            }
+            fs::write(&output.path, &output.contents)
+                .with_context(|| format!("Could not write {}", output.path.display()))?;
+
+            #[cfg(unix)]
+            if let Some(mode) = output.mode {
+                use std::os::unix::fs::PermissionsExt;
+
+                fs::set_permissions(&output.path, fs::Permissions::from_mode(mode)).with_context(
+                    || format!("Could not set permissions of {}", output.path.display()),
+                )?;
+            }
+        }
+
+        Ok(outputs.into_iter().map(|output| output.path).collect())
+    }
+}
+
+/// Renders an output path pattern for a palette and a template.
+fn output_path(pattern: &str, palette: &Base16Palette, template_path: &Path) -> Result<PathBuf> {
+    let template_name = template_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let template_name = template_name
+        .strip_suffix(".liquid")
+        .unwrap_or(template_name);
+
+    let globals = liquid::object!({
+        "palette": {
+            "name": palette.name,
+            "slug": slug(&palette.name),
+        },
+        "template": {
+            "name": template_name,
+        },
+    });
+
+    let path = ParserBuilder::with_stdlib()
+        .build()?
+        .parse(pattern)
+        .and_then(|pattern| pattern.render(&globals))
+        .with_context(|| format!("Could not render output path \"{}\"", pattern))?;
+
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    use rstest::*;
+
+    const PALETTE_YAML: &str = include_str!("../examples/colorschemes/selenized_light.yaml");
+
+    #[rstest]
+    fn test_build(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("light.yaml", PALETTE_YAML)?;
+        tmpdir.write_to_file("dark.yaml", "extends: light.yaml\nname: Selenized dark\n")?;
+        tmpdir.write_to_file("bg.txt.liquid", "{{ palette.name }}: #{{ bg_0 }}")?;
+        tmpdir.write_to_file("name.txt.liquid", "{{ palette.name }}")?;
+        let manifest_path = tmpdir.write_to_file(
+            "base16cs.toml",
+            r#"
+palettes = ["light.yaml", "dark.yaml"]
+unroll_colors_hex = true
+
+[[templates]]
+path = "bg.txt.liquid"
+output = "out/{{ palette.slug }}/{{ template.name }}"
+
+[[templates]]
+path = "name.txt.liquid"
+output = "out/{{ palette.slug }}/name.txt"
+"#,
+        )?;
+
+        let written = Manifest::from_path(&manifest_path)?.build()?;
+        assert_eq!(written.len(), 4);
+
+        let out = tmpdir.tmpdir.path().join("out");
+        assert_eq!(
+            fs::read_to_string(out.join("selenized-light/bg.txt"))?,
+            "Selenized light: #fef3da"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("selenized-dark/name.txt"))?,
+            "Selenized dark"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_build_front_matter(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("light.yaml", PALETTE_YAML)?;
+        tmpdir.write_to_file(
+            "dark.yaml",
+            &PALETTE_YAML
+                .replace("Selenized light", "Selenized dark")
+                .replace("l: 96.0", "l: 10.0"),
+        )?;
+        tmpdir.write_to_file(
+            "dark.sh.liquid",
+            "---\noutput: \"out/{{ palette.slug }}.sh\"\nmode: \"0755\"\nvariant: dark\n---\necho {{ palette.name }}\n",
+        )?;
+        let manifest_path = tmpdir.write_to_file(
+            "base16cs.toml",
+            r#"
+palettes = ["light.yaml", "dark.yaml"]
+
+[[templates]]
+path = "dark.sh.liquid"
+"#,
+        )?;
+
+        let written = Manifest::from_path(&manifest_path)?.build()?;
+        let out = tmpdir.tmpdir.path().join("out/selenized-dark.sh");
+        assert_eq!(written, vec![out.clone()]);
+        assert_eq!(fs::read_to_string(&out)?, "echo Selenized dark\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&out)?.permissions().mode() & 0o777, 0o755);
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_build_no_output(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("light.yaml", PALETTE_YAML)?;
+        tmpdir.write_to_file("name.txt.liquid", "{{ palette.name }}")?;
+        let manifest_path = tmpdir.write_to_file(
+            "base16cs.toml",
+            r#"
+palettes = ["light.yaml"]
+
+[[templates]]
+path = "name.txt.liquid"
+"#,
+        )?;
+
+        let err = Manifest::from_path(&manifest_path)?.build().unwrap_err();
+        assert!(err.to_string().contains("has no output path"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_build_output_conflict(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("light.yaml", PALETTE_YAML)?;
+        tmpdir.write_to_file("dark.yaml", "extends: light.yaml\nname: Selenized dark\n")?;
+        tmpdir.write_to_file("name.txt.liquid", "{{ palette.name }}")?;
+        let manifest_path = tmpdir.write_to_file(
+            "base16cs.toml",
+            r#"
+palettes = ["light.yaml", "dark.yaml"]
+
+[[templates]]
+path = "name.txt.liquid"
+output = "out/name.txt"
+"#,
+        )?;
+
+        let err = Manifest::from_path(&manifest_path)?.build().unwrap_err();
+        assert!(err.to_string().starts_with("Output path "));
+        assert!(!tmpdir.tmpdir.path().join("out").exists());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_partial_precedence(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("light.yaml", PALETTE_YAML)?;
+        tmpdir.write_to_file("name.txt.liquid", "{% include \"name.liquid\" %}")?;
+        for dir in ["common", "theme"] {
+            fs::create_dir(tmpdir.tmpdir.path().join(dir))?;
+            tmpdir.write_to_file(&format!("{}/name.liquid", dir), dir)?;
+        }
+        let manifest_path = tmpdir.write_to_file(
+            "base16cs.toml",
+            r#"
+palettes = ["light.yaml"]
+partials_dirs = ["common", "theme"]
+partial_precedence = "first"
+
+[[templates]]
+path = "name.txt.liquid"
+output = "name.txt"
+"#,
+        )?;
+
+        let mut manifest = Manifest::from_path(&manifest_path)?;
+        assert_eq!(manifest.partials()?.conflicts().len(), 1);
+        assert_eq!(manifest.render()?[0].contents, "common");
+
+        manifest.deny_partial_conflicts = true;
+        let Err(err) = manifest.render() else {
+            panic!("Should not have rendered with conflicting partials");
+        };
+        assert!(err.to_string().starts_with("Conflicting partials: "));
+
+        Ok(())
+    }
+}
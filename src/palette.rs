@@ -1,11 +1,22 @@
+use anyhow::{anyhow, bail, Result};
 use arrayvec::ArrayVec;
 use num_traits::cast::AsPrimitive;
 use palette::rgb::Srgb;
-use palette::{lab::Lab, IntoColor};
-use serde::{Deserialize, Serialize};
+use palette::{lab::Lab, FromColor, Hsl, IntoColor, Xyz};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 
 /// A base color in its canonical form.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+///
+/// A base color is always serialized with its CIE L*a*b* values, but can be
+/// deserialized from any one of these inputs, which are converted to CIE
+/// L*a*b*:
+/// - `lab: {l, a, b}`
+/// - `hex: "#fef3da"` (the leading `#` is optional)
+/// - `srgb: {red, green, blue}` (0-255)
+/// - `hsl: {hue, saturation, lightness}` (hue in degrees, others in 0.0-1.0)
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(try_from = "BaseColorInput")]
 pub struct BaseColor {
     /// This base color's canonical name.
     pub name: String,
@@ -26,10 +37,102 @@ impl BaseColor {
             lab: Lab::new(l.as_(), a.as_(), b.as_()),
         }
     }
+
+    /// Instantiates a base color by converting the given sRGB values to CIE L*a*b*.
+    pub fn from_srgb<S>(name: S, srgb: Srgb<u8>) -> BaseColor
+    where
+        S: Into<String>,
+    {
+        let srgb: Srgb = srgb.into_format();
+        BaseColor {
+            name: name.into(),
+            lab: srgb.into_color(),
+        }
+    }
+
+    /// Returns the WCAG 2 contrast ratio between this color and another, from
+    /// 1.0 (none) to 21.0 (black on white).
+    pub fn contrast_ratio(&self, other: &BaseColor) -> f32 {
+        contrast_ratio(&self.lab, &other.lab)
+    }
+}
+
+/// Returns the WCAG 2 relative luminance of a CIE L*a*b* color, as displayed
+/// (i.e. clamped to the sRGB gamut).
+pub(crate) fn relative_luminance(lab: &Lab) -> f32 {
+    let srgb: Srgb = (*lab).into_color();
+    Xyz::from_color(srgb).y
+}
+
+/// Returns the WCAG 2 contrast ratio between two CIE L*a*b* colors.
+pub(crate) fn contrast_ratio(a: &Lab, b: &Lab) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// A base color as written in a palette file, before its conversion to CIE L*a*b*.
+#[derive(Deserialize)]
+struct BaseColorInput {
+    name: String,
+
+    #[serde(default, deserialize_with = "some")]
+    lab: Option<Lab>,
+
+    #[serde(default, deserialize_with = "some")]
+    hex: Option<String>,
+
+    #[serde(default, deserialize_with = "some")]
+    srgb: Option<Srgb<u8>>,
+
+    #[serde(default, deserialize_with = "some")]
+    hsl: Option<Hsl>,
+}
+
+/// Deserializes a present optional field from its bare value, rather than from
+/// an explicit `Some(...)` (as RON would otherwise expect).
+pub(crate) fn some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl TryFrom<BaseColorInput> for BaseColor {
+    type Error = anyhow::Error;
+
+    fn try_from(input: BaseColorInput) -> Result<Self> {
+        let BaseColorInput {
+            name,
+            lab,
+            hex,
+            srgb,
+            hsl,
+        } = input;
+
+        match (lab, hex, srgb, hsl) {
+            (Some(lab), None, None, None) => Ok(BaseColor { name, lab }),
+            (None, Some(hex), None, None) => {
+                let srgb = hex.parse().map_err(|err| {
+                    anyhow!("Invalid hex \"{}\" for color {}: {}", hex, name, err)
+                })?;
+                Ok(BaseColor::from_srgb(name, srgb))
+            }
+            (None, None, Some(srgb), None) => Ok(BaseColor::from_srgb(name, srgb)),
+            (None, None, None, Some(hsl)) => Ok(BaseColor {
+                name,
+                lab: Srgb::from_color(hsl).into_color(),
+            }),
+            _ => Err(anyhow!(
+                "Expected exactly one of lab, hex, srgb or hsl for color {}",
+                name
+            )),
+        }
+    }
 }
 
 /// A palette is a collection of base colors (in their canonical forms only).
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Palette<const N: usize> {
     /// This palette's name.
     pub name: String,
@@ -63,6 +166,23 @@ pub struct Palette<const N: usize> {
 pub type Base16Palette = Palette<16>;
 pub type Base16Colors = [BaseColor; 16];
 
+/// Returns the Base16 role name of the color slot at `index` in a palette.
+///
+/// Slots are named by their two-digit hex index, e.g. `base00` or `base0d`.
+pub fn base16_role(index: usize) -> String {
+    format!("base{:02x}", index)
+}
+
+/// Slugifies a palette name into lowercase alphanumeric words joined by `-`,
+/// e.g. `Selenized light` into `selenized-light`.
+pub fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 impl<const N: usize> Palette<N> {
     #[inline]
     pub fn new<S>(name: S, colors: [BaseColor; N]) -> Palette<N>
@@ -74,6 +194,45 @@ impl<const N: usize> Palette<N> {
             colors,
         }
     }
+
+    /// Instantiates a palette from a list of colors, which must be exactly N long.
+    pub(crate) fn from_vec<S>(name: S, colors: Vec<BaseColor>) -> Result<Palette<N>>
+    where
+        S: Into<String>,
+    {
+        let len = colors.len();
+        let colors: [BaseColor; N] = colors
+            .try_into()
+            .map_err(|_| anyhow!("Expected a palette of {} colors, found {}", N, len))?;
+
+        Ok(Palette::new(name, colors))
+    }
+
+    /// Returns this palette with one of its colors overridden with the given
+    /// CIE L*a*b* values, e.g. to preview a tweak. The color keeps its name.
+    ///
+    /// The color is given by its name, which must be unique in the palette,
+    /// or else by its Base16 role (e.g. `base0d`, see [`base16_role`]).
+    pub fn with_override(mut self, name: &str, lab: Lab) -> Result<Palette<N>> {
+        let index = self.color_index(name)?;
+        self.colors[index].lab = lab;
+
+        Ok(self)
+    }
+
+    /// Finds the index of a color by its (unique) name, or by its Base16 role.
+    fn color_index(&self, name: &str) -> Result<usize> {
+        let mut matches = (0..N).filter(|&index| self.colors[index].name == name);
+        match (matches.next(), matches.next()) {
+            (Some(index), None) => Ok(index),
+            (Some(_), Some(_)) => {
+                bail!("Color name {} is ambiguous in palette {}", name, self.name)
+            }
+            (None, _) => (0..N)
+                .find(|&index| base16_role(index) == name)
+                .ok_or_else(|| anyhow!("No color named {} in palette {}", name, self.name)),
+        }
+    }
 }
 
 /// A color with derived forms (sRGB values derived from its canonical CIE Lab).
@@ -133,3 +292,161 @@ impl<'a, const N: usize> From<&'a Palette<N>> for DerivedPalette<'a, N> {
         }
     }
 }
+
+/// An owned counterpart of a `DerivedColor`, which (unlike the latter) can be
+/// deserialized.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct OwnedDerivedColor {
+    /// This color's canonical form, as well as its name.
+    pub base: BaseColor,
+
+    /// This color's (stored) derived sRGB values form.
+    pub srgb: Srgb<u8>,
+
+    /// This color's (stored) derived sRGB values, in stringified hex form ("{:x}").
+    pub srgb_hex: String,
+}
+
+impl From<&DerivedColor<'_>> for OwnedDerivedColor {
+    fn from(derived: &DerivedColor) -> Self {
+        Self {
+            base: derived.base.clone(),
+            srgb: derived.srgb,
+            srgb_hex: derived.srgb_hex.clone(),
+        }
+    }
+}
+
+/// An owned counterpart of a `DerivedPalette`, e.g. as read back from a
+/// serialized derived palette.
+///
+/// Its stored sRGB values are not trusted: see [`OwnedDerivedPalette::verify`].
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct OwnedDerivedPalette<const N: usize> {
+    /// This palette's name.
+    pub name: String,
+
+    /// The derived colors in this palette.
+    #[serde(with = "serde_arrays")]
+    pub colors: [OwnedDerivedColor; N],
+}
+
+pub type Base16OwnedDerivedPalette = OwnedDerivedPalette<16>;
+
+/// A color whose stored sRGB values differ from those derived from its CIE Lab.
+#[derive(PartialEq, Debug)]
+pub struct DerivationMismatch {
+    /// The index of the color in its palette.
+    pub index: usize,
+
+    /// The color's name.
+    pub name: String,
+
+    /// The stored sRGB values, in stringified hex form.
+    pub stored_hex: String,
+
+    /// The sRGB values freshly derived from the color's CIE Lab, in stringified hex form.
+    pub derived_hex: String,
+}
+
+impl fmt::Display for DerivationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): stored sRGB #{} does not match #{} derived from L*a*b*",
+            base16_role(self.index),
+            self.name,
+            self.stored_hex,
+            self.derived_hex
+        )
+    }
+}
+
+impl<const N: usize> OwnedDerivedPalette<N> {
+    /// Re-derives each color's sRGB values from its CIE Lab, and reports every
+    /// color whose stored `srgb` or `srgb_hex` does not match.
+    ///
+    /// An empty list means this derived palette is consistent with its base.
+    pub fn verify(&self) -> Vec<DerivationMismatch> {
+        self.colors
+            .iter()
+            .enumerate()
+            .filter_map(|(index, color)| {
+                let derived = DerivedColor::from(&color.base);
+                let stored_hex = color.srgb_hex.trim_start_matches('#');
+                let matches = color.srgb == derived.srgb
+                    && stored_hex.eq_ignore_ascii_case(&derived.srgb_hex);
+
+                (!matches).then(|| DerivationMismatch {
+                    index,
+                    name: color.base.name.clone(),
+                    stored_hex: stored_hex.to_string(),
+                    derived_hex: derived.srgb_hex,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the base palette this derived palette was (supposedly) derived from.
+    pub fn to_palette(&self) -> Palette<N> {
+        Palette {
+            name: self.name.clone(),
+            colors: self.colors.clone().map(|color| color.base),
+        }
+    }
+}
+
+impl<const N: usize> From<&DerivedPalette<'_, N>> for OwnedDerivedPalette<N> {
+    fn from(derived: &DerivedPalette<N>) -> Self {
+        Self {
+            name: derived.name.to_string(),
+            colors: derived.colors.each_ref().map(OwnedDerivedColor::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<3> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+                BaseColor::new("fg", 42, -6, -6),
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_slug() {
+        assert_eq!(slug("Selenized light"), "selenized-light");
+        assert_eq!(slug("  Solarized (Dark) v2 "), "solarized-dark-v2");
+    }
+
+    #[rstest]
+    #[case::name("bg", 0)]
+    #[case::role("base02", 2)]
+    fn test_with_override(palette: Palette<3>, #[case] name: &str, #[case] index: usize) {
+        let lab = Lab::new(50.0, 10.0, -10.0);
+
+        let overridden = palette.clone().with_override(name, lab).unwrap();
+        assert_eq!(overridden.colors[index].name, palette.colors[index].name);
+        assert_eq!(overridden.colors[index].lab, lab);
+        assert_eq!(overridden.colors[1], palette.colors[1]);
+    }
+
+    #[rstest]
+    #[case::ambiguous("fg")]
+    #[case::unknown("blue")]
+    fn test_with_override_invalid(palette: Palette<3>, #[case] name: &str) {
+        assert!(palette
+            .with_override(name, Lab::new(0.0, 0.0, 0.0))
+            .is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::palette::{base16_role, DerivedColor};
+
+/// Layout options for a swatch sheet.
+pub struct SwatchOptions {
+    /// How many color tiles to lay out per row.
+    pub columns: usize,
+
+    /// The width of each color tile, in pixels.
+    pub tile_width: u32,
+
+    /// The height of each color tile, in pixels.
+    pub tile_height: u32,
+
+    /// The gap between tiles and around the sheet's edges, in pixels.
+    pub gap: u32,
+}
+
+impl Default for SwatchOptions {
+    fn default() -> Self {
+        Self {
+            columns: 8,
+            tile_width: 160,
+            tile_height: 120,
+            gap: 16,
+        }
+    }
+}
+
+impl SwatchOptions {
+    /// The number of tile rows needed to lay out `count` colors.
+    fn rows(&self, count: usize) -> usize {
+        count.div_ceil(self.columns.max(1))
+    }
+
+    /// The width of a single palette's grid of tiles, including outer gaps.
+    fn grid_width(&self, count: usize) -> u32 {
+        let columns = self.columns.max(1).min(count.max(1)) as u32;
+        columns * self.tile_width + (columns + 1) * self.gap
+    }
+
+    /// The height of a single palette's grid of tiles, including outer gaps.
+    fn grid_height(&self, count: usize) -> u32 {
+        let rows = self.rows(count) as u32;
+        rows * self.tile_height + (rows + 1) * self.gap
+    }
+
+    /// The top-left position of the tile at `index`, relative to its grid.
+    fn tile_origin(&self, index: usize) -> (u32, u32) {
+        let columns = self.columns.max(1);
+        let (row, column) = ((index / columns) as u32, (index % columns) as u32);
+        (
+            self.gap + column * (self.tile_width + self.gap),
+            self.gap + row * (self.tile_height + self.gap),
+        )
+    }
+}
+
+/// The lines of text labeling a color tile: name, role, hex and L*a*b* values.
+fn tile_labels(index: usize, color: &DerivedColor) -> [String; 4] {
+    let lab = &color.base.lab;
+    [
+        color.base.name.clone(),
+        base16_role(index),
+        format!("#{}", color.srgb_hex),
+        format!("lab({} {} {})", lab.l, lab.a, lab.b),
+    ]
+}
+
+/// Whether text drawn over a color of lightness `l` should be dark (or light).
+fn wants_dark_text(l: f32) -> bool {
+    l > 60.0
+}
+
+pub mod svg;
+
+#[cfg(feature = "png")]
+pub mod png;
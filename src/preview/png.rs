@@ -0,0 +1,330 @@
+use anyhow::{Context, Result};
+use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_6X13_BOLD, FONT_7X13};
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+use std::convert::Infallible;
+
+use crate::palette::{Base16DerivedPalette, DerivedColor, DerivedPalette};
+use crate::preview::{tile_labels, wants_dark_text, SwatchOptions};
+
+/// The height of the title band above the grid of tiles.
+const TITLE_HEIGHT: u32 = 40;
+
+/// Renders a swatch sheet of the given derived palette as a PNG image.
+///
+/// This is the raster counterpart of [`svg::swatch_sheet`](crate::svg::swatch_sheet),
+/// drawn with a bundled bitmap font.
+pub fn swatch_sheet<const N: usize>(
+    palette: &DerivedPalette<N>,
+    options: &SwatchOptions,
+) -> Result<Vec<u8>> {
+    let mut canvas = Canvas::new(
+        options.grid_width(N),
+        TITLE_HEIGHT + options.grid_height(N),
+        palette.colors.first().map_or(Rgb888::WHITE, rgb),
+    );
+
+    let title_color = palette.colors.first().map_or(Rgb888::BLACK, text_color);
+    canvas.text(
+        palette.name,
+        options.gap,
+        TITLE_HEIGHT - 26,
+        &FONT_6X13_BOLD,
+        title_color,
+    );
+
+    for (index, color) in palette.colors.iter().enumerate() {
+        let (tile_x, tile_y) = options.tile_origin(index);
+        let tile_y = tile_y + TITLE_HEIGHT;
+        canvas.rect(
+            tile_x,
+            tile_y,
+            options.tile_width,
+            options.tile_height,
+            rgb(color),
+        );
+
+        for (line, label) in tile_labels(index, color).iter().enumerate() {
+            let font = if line == 0 {
+                &FONT_6X13_BOLD
+            } else {
+                &FONT_6X10
+            };
+            canvas.text(
+                label,
+                tile_x + 8,
+                tile_y + 8 + 16 * line as u32,
+                font,
+                text_color(color),
+            );
+        }
+    }
+
+    canvas.encode()
+}
+
+/// Renders a short syntax-highlighted code sample as a PNG image, colored by
+/// the Base16 role assignments of the given palette.
+pub fn code_sample(palette: &Base16DerivedPalette) -> Result<Vec<u8>> {
+    const GUTTER_WIDTH: u32 = 40;
+    const LINE_HEIGHT: u32 = 18;
+    const PADDING: u32 = 12;
+
+    let glyph_width = FONT_7X13.character_size.width + FONT_7X13.character_spacing;
+    let columns = CODE_SAMPLE
+        .iter()
+        .map(|line| line.iter().map(|(_, text)| text.len()).sum::<usize>())
+        .max()
+        .unwrap_or(0) as u32;
+
+    let width = GUTTER_WIDTH + 2 * PADDING + columns * glyph_width;
+    let height = 2 * PADDING + CODE_SAMPLE.len() as u32 * LINE_HEIGHT;
+    let colors = &palette.colors;
+
+    let mut canvas = Canvas::new(width, height, rgb(&colors[0x00]));
+    canvas.rect(0, 0, GUTTER_WIDTH, height, rgb(&colors[0x01]));
+
+    for (number, line) in CODE_SAMPLE.iter().enumerate() {
+        let y = PADDING + number as u32 * LINE_HEIGHT;
+        canvas.text(
+            &format!("{:>3}", number + 1),
+            PADDING / 2,
+            y,
+            &FONT_7X13,
+            rgb(&colors[0x04]),
+        );
+
+        let mut x = GUTTER_WIDTH + PADDING;
+        for (role, text) in line.iter() {
+            canvas.text(text, x, y, &FONT_7X13, rgb(&colors[*role]));
+            x += text.len() as u32 * glyph_width;
+        }
+    }
+
+    canvas.encode()
+}
+
+/// A sample Rust snippet, as lines of (Base16 role index, text) spans.
+const CODE_SAMPLE: &[&[(usize, &str)]] = &[
+    &[(0x03, "// Greets everyone on the list.")],
+    &[
+        (0x0e, "fn "),
+        (0x0d, "greet"),
+        (0x05, "("),
+        (0x08, "names"),
+        (0x05, ": &["),
+        (0x0a, "String"),
+        (0x05, "]) -> "),
+        (0x0a, "usize"),
+        (0x05, " {"),
+    ],
+    &[
+        (0x05, "    "),
+        (0x0e, "let mut "),
+        (0x08, "count"),
+        (0x05, " = "),
+        (0x09, "0"),
+        (0x05, ";"),
+    ],
+    &[
+        (0x05, "    "),
+        (0x0e, "for "),
+        (0x08, "name"),
+        (0x0e, " in "),
+        (0x08, "names"),
+        (0x05, " {"),
+    ],
+    &[
+        (0x05, "        "),
+        (0x0f, "println!"),
+        (0x05, "("),
+        (0x0b, "\"Hello, {}!"),
+        (0x0c, "\\n"),
+        (0x0b, "\""),
+        (0x05, ", "),
+        (0x08, "name"),
+        (0x05, ");"),
+    ],
+    &[
+        (0x05, "        "),
+        (0x08, "count"),
+        (0x05, " += "),
+        (0x09, "1"),
+        (0x05, ";"),
+    ],
+    &[(0x05, "    }")],
+    &[(0x05, "    "), (0x08, "count")],
+    &[(0x05, "}")],
+];
+
+/// An in-memory RGB pixel buffer that can be drawn on and encoded as PNG.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: Rgb888) -> Self {
+        let pixels =
+            [background.r(), background.g(), background.b()].repeat((width * height) as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Rgb888) {
+        let Ok(()) = Rectangle::new(point(x, y), Size::new(width, height))
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(self);
+    }
+
+    fn text(&mut self, text: &str, x: u32, y: u32, font: &MonoFont, color: Rgb888) {
+        let style = MonoTextStyle::new(font, color);
+        let Ok(_) = Text::with_baseline(text, point(x, y), style, Baseline::Top).draw(self);
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut writer = encoder
+                .write_header()
+                .context("Could not write PNG header")?;
+            writer
+                .write_image_data(&self.pixels)
+                .context("Could not write PNG image data")?;
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(coord.x), u32::try_from(coord.y)) else {
+                continue;
+            };
+            if x < self.width && y < self.height {
+                let offset = 3 * (y * self.width + x) as usize;
+                self.pixels[offset..offset + 3].copy_from_slice(&[color.r(), color.g(), color.b()]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn point(x: u32, y: u32) -> Point {
+    Point::new(x as i32, y as i32)
+}
+
+fn rgb(color: &DerivedColor) -> Rgb888 {
+    Rgb888::new(color.srgb.red, color.srgb.green, color.srgb.blue)
+}
+
+fn text_color(color: &DerivedColor) -> Rgb888 {
+    if wants_dark_text(color.base.lab.l) {
+        Rgb888::BLACK
+    } else {
+        Rgb888::WHITE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{Base16Palette, BaseColor, Palette};
+
+    use rstest::*;
+
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[fixture]
+    fn palette() -> Base16Palette {
+        Palette::new(
+            "Selenized light",
+            [
+                BaseColor::new("bg_0", 96, 0, 13),
+                BaseColor::new("bg_1", 91, 0, 13),
+                BaseColor::new("bg_2", 82, 0, 13),
+                BaseColor::new("dim_0", 62, -4, 1),
+                BaseColor::new("fg_0", 42, -6, -6),
+                BaseColor::new("fg_1", 31, -6, -6),
+                BaseColor::new("unused_0", 28, -13, -13),
+                BaseColor::new("unused_1", 23, -12, -12),
+                BaseColor::new("red", 46, 66, 42),
+                BaseColor::new("orange", 52, 39, 52),
+                BaseColor::new("magenta", 52, 58, -16),
+                BaseColor::new("green", 54, -40, 58),
+                BaseColor::new("cyan", 57, -42, -4),
+                BaseColor::new("blue", 46, 0, -60),
+                BaseColor::new("yellow", 59, 6, 71),
+                BaseColor::new("violet", 49, 32, -47),
+            ],
+        )
+    }
+
+    /// Decodes a PNG into its (width, height, RGB pixels).
+    fn decode(bytes: &[u8]) -> (u32, u32, Vec<u8>) {
+        let decoder = png::Decoder::new(bytes);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        (info.width, info.height, pixels)
+    }
+
+    #[rstest]
+    fn test_swatch_sheet(palette: Base16Palette) -> Result<()> {
+        let derived = DerivedPalette::from(&palette);
+        let options = SwatchOptions::default();
+        let bytes = swatch_sheet(&derived, &options)?;
+        assert!(bytes.starts_with(PNG_SIGNATURE));
+
+        let (width, height, pixels) = decode(&bytes);
+        assert_eq!((width, height), (1424, 328));
+
+        // The top-right corner of the second tile ("bg_1") is filled with its color.
+        let (x, y) = options.tile_origin(1);
+        let offset = 3 * ((y + TITLE_HEIGHT) * width + x + options.tile_width - 1) as usize;
+        assert_eq!(&pixels[offset..offset + 3], &[240, 228, 204]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_code_sample(palette: Base16Palette) -> Result<()> {
+        let derived = DerivedPalette::from(&palette);
+        let bytes = code_sample(&derived)?;
+        assert!(bytes.starts_with(PNG_SIGNATURE));
+
+        // The gutter is base01 and the code background is base00.
+        let (width, _, pixels) = decode(&bytes);
+        assert_eq!(&pixels[0..3], &[240, 228, 204]);
+        let offset = 3 * (width - 1) as usize;
+        assert_eq!(&pixels[offset..offset + 3], &[254, 243, 218]);
+
+        Ok(())
+    }
+}
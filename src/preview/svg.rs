@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use crate::export::escape_xml;
+use crate::palette::DerivedPalette;
+use crate::preview::{tile_labels, wants_dark_text, SwatchOptions};
+
+/// The height of the title band above each palette's grid of tiles.
+const TITLE_HEIGHT: u32 = 40;
+
+/// Renders a swatch sheet of the given derived palette as an SVG document.
+///
+/// Each color is drawn as a tile labeled with its name, Base16 role, sRGB hex
+/// and L*a*b* values. The sheet's background is the palette's first color.
+pub fn swatch_sheet<const N: usize>(
+    palette: &DerivedPalette<N>,
+    options: &SwatchOptions,
+) -> String {
+    let width = options.grid_width(N);
+    let height = TITLE_HEIGHT + options.grid_height(N);
+
+    let mut svg = open_document(width, height);
+    write_palette_group(&mut svg, palette, options, 0);
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Renders two derived palettes (e.g., light and dark variants) side by side
+/// as a single SVG document.
+pub fn swatch_sheet_pair<const N: usize>(
+    left: &DerivedPalette<N>,
+    right: &DerivedPalette<N>,
+    options: &SwatchOptions,
+) -> String {
+    let grid_width = options.grid_width(N);
+    let width = 2 * grid_width;
+    let height = TITLE_HEIGHT + options.grid_height(N);
+
+    let mut svg = open_document(width, height);
+    write_palette_group(&mut svg, left, options, 0);
+    write_palette_group(&mut svg, right, options, grid_width);
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn open_document(width: u32, height: u32) -> String {
+    format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" ",
+            "width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" ",
+            "font-family=\"monospace\" font-size=\"12\">\n",
+        ),
+        w = width,
+        h = height,
+    )
+}
+
+/// Writes one palette's title band and grid of tiles, offset horizontally by `x`.
+fn write_palette_group<const N: usize>(
+    svg: &mut String,
+    palette: &DerivedPalette<N>,
+    options: &SwatchOptions,
+    x: u32,
+) {
+    let width = options.grid_width(N);
+    let height = TITLE_HEIGHT + options.grid_height(N);
+    let (background, title_fill) = match palette.colors.first() {
+        Some(color) => (format!("#{}", color.srgb_hex), text_fill(color.base.lab.l)),
+        None => ("#ffffff".to_string(), text_fill(100.0)),
+    };
+
+    writeln!(
+        svg,
+        "<g transform=\"translate({} 0)\">\n<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+        x, width, height, background
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" fill=\"{}\" font-size=\"18\" font-weight=\"bold\">{}</text>",
+        options.gap,
+        TITLE_HEIGHT - 10,
+        title_fill,
+        escape_xml(palette.name)
+    )
+    .unwrap();
+
+    for (index, color) in palette.colors.iter().enumerate() {
+        let (tile_x, tile_y) = options.tile_origin(index);
+        let tile_y = tile_y + TITLE_HEIGHT;
+        let fill = text_fill(color.base.lab.l);
+
+        writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"#{}\"/>",
+            tile_x, tile_y, options.tile_width, options.tile_height, color.srgb_hex
+        )
+        .unwrap();
+        for (line, label) in tile_labels(index, color).iter().enumerate() {
+            let weight = if line == 0 {
+                " font-weight=\"bold\""
+            } else {
+                ""
+            };
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\"{}>{}</text>",
+                tile_x + 8,
+                tile_y + 20 + 16 * line as u32,
+                fill,
+                weight,
+                escape_xml(label)
+            )
+            .unwrap();
+        }
+    }
+
+    svg.push_str("</g>\n");
+}
+
+fn text_fill(l: f32) -> &'static str {
+    if wants_dark_text(l) {
+        "#000000"
+    } else {
+        "#ffffff"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{BaseColor, Palette};
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "Light & Co",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_swatch_sheet(palette: Palette<2>) {
+        let derived = DerivedPalette::from(&palette);
+        let svg = swatch_sheet(&derived, &SwatchOptions::default());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"368\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">Light &amp; Co</text>"));
+        assert!(svg.contains("fill=\"#fef3da\"/>"));
+        assert!(svg.contains(">base01</text>"));
+        assert!(svg.contains(">#384c52</text>"));
+        assert!(svg.contains(">lab(31 -6 -6)</text>"));
+    }
+
+    #[rstest]
+    fn test_swatch_sheet_pair(palette: Palette<2>) {
+        let derived = DerivedPalette::from(&palette);
+        let svg = swatch_sheet_pair(&derived, &derived, &SwatchOptions::default());
+
+        assert!(svg.contains("width=\"736\""));
+        assert!(svg.contains("<g transform=\"translate(0 0)\">"));
+        assert!(svg.contains("<g transform=\"translate(368 0)\">"));
+    }
+
+    #[rstest]
+    fn test_escape() {
+        assert_eq!(
+            escape_xml("<a & 'b'>\""),
+            "&lt;a &amp; &apos;b&apos;&gt;&quot;"
+        );
+    }
+}
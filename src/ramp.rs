@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Result};
+use palette::convert::FromColorUnclamped;
+use palette::{lab::Lab, Lch, Oklab};
+use std::str::FromStr;
+
+use crate::palette::{BaseColor, Palette};
+
+/// The colorspace in which to interpolate between colors.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum Interpolation {
+    /// CIE L*a*b*, the palette's canonical colorspace.
+    #[default]
+    Lab,
+    /// CIE LCh, the cylindrical form of CIE L*a*b*, which keeps chroma and
+    /// rotates hue along its shortest path.
+    Lch,
+    /// OKLab, whose ramps are perceptually more even in lightness.
+    Oklab,
+}
+
+impl FromStr for Interpolation {
+    type Err = anyhow::Error;
+
+    /// Parses an interpolation colorspace from its (lowercase) name.
+    fn from_str(s: &str) -> Result<Interpolation> {
+        match s {
+            "lab" => Ok(Interpolation::Lab),
+            "lch" => Ok(Interpolation::Lch),
+            "oklab" => Ok(Interpolation::Oklab),
+            _ => Err(anyhow!("Unknown interpolation colorspace: \"{}\"", s)),
+        }
+    }
+}
+
+impl BaseColor {
+    /// Interpolates between this color and another in the given colorspace,
+    /// `t` being in [0.0, 1.0].
+    pub fn mix(&self, other: &BaseColor, t: f32, interpolation: Interpolation) -> Lab {
+        mix(self.lab, other.lab, t, interpolation)
+    }
+
+    /// Returns a ramp of `steps` evenly spaced colors from this color to
+    /// another (both included), interpolated in the given colorspace.
+    ///
+    /// Ramp colors are named after their ends and their index, e.g.
+    /// `bg_0_blue_3`.
+    pub fn ramp(
+        &self,
+        to: &BaseColor,
+        steps: usize,
+        interpolation: Interpolation,
+    ) -> Vec<BaseColor> {
+        (0..steps)
+            .map(|index| {
+                let t = if steps > 1 {
+                    index as f32 / (steps - 1) as f32
+                } else {
+                    0.0
+                };
+                BaseColor {
+                    name: format!("{}_{}_{}", self.name, to.name, index),
+                    lab: self.mix(to, t, interpolation),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> Palette<N> {
+    /// Returns a ramp of `steps` evenly spaced colors between two of this
+    /// palette's colors, given by name. See [`BaseColor::ramp`].
+    pub fn ramp(
+        &self,
+        from: &str,
+        to: &str,
+        steps: usize,
+        interpolation: Interpolation,
+    ) -> Result<Vec<BaseColor>> {
+        let find = |name: &str| {
+            self.colors
+                .iter()
+                .find(|color| color.name == name)
+                .ok_or_else(|| anyhow!("No color named {} in palette {}", name, self.name))
+        };
+
+        Ok(find(from)?.ramp(find(to)?, steps, interpolation))
+    }
+}
+
+/// Interpolates between two colors in the given colorspace, `t` being in [0.0, 1.0].
+pub(crate) fn mix(from: Lab, to: Lab, t: f32, interpolation: Interpolation) -> Lab {
+    let lerp = |from: f32, to: f32| from + (to - from) * t;
+
+    match interpolation {
+        Interpolation::Lab => Lab::new(lerp(from.l, to.l), lerp(from.a, to.a), lerp(from.b, to.b)),
+        Interpolation::Lch => {
+            let from = Lch::from_color_unclamped(from);
+            let to = Lch::from_color_unclamped(to);
+            // The hue of an achromatic color is meaningless: take the other's.
+            let (from_hue, to_hue) = match (from.chroma < 1e-3, to.chroma < 1e-3) {
+                (true, false) => (to.hue.into_degrees(), to.hue.into_degrees()),
+                (false, true) => (from.hue.into_degrees(), from.hue.into_degrees()),
+                _ => (from.hue.into_degrees(), to.hue.into_degrees()),
+            };
+            // Rotate along the shortest path, within [-180.0, 180.0) degrees.
+            let delta = (to_hue - from_hue + 180.0).rem_euclid(360.0) - 180.0;
+
+            Lab::from_color_unclamped(Lch::new(
+                lerp(from.l, to.l),
+                lerp(from.chroma, to.chroma),
+                from_hue + delta * t,
+            ))
+        }
+        Interpolation::Oklab => {
+            let from = Oklab::from_color_unclamped(from);
+            let to = Oklab::from_color_unclamped(to);
+            Lab::from_color_unclamped(Oklab::new(
+                lerp(from.l, to.l),
+                lerp(from.a, to.a),
+                lerp(from.b, to.b),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<3> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+                BaseColor::new("blue", 46, 0, -60),
+            ],
+        )
+    }
+
+    fn assert_lab_eq(lab: Lab, expected: Lab) {
+        assert!(
+            (lab.l - expected.l).abs() < 1e-3
+                && (lab.a - expected.a).abs() < 1e-3
+                && (lab.b - expected.b).abs() < 1e-3,
+            "{:?} != {:?}",
+            lab,
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::lab(Interpolation::Lab)]
+    #[case::lch(Interpolation::Lch)]
+    #[case::oklab(Interpolation::Oklab)]
+    fn test_ramp_ends(palette: Palette<3>, #[case] interpolation: Interpolation) -> Result<()> {
+        let ramp = palette.ramp("bg", "blue", 5, interpolation)?;
+        assert_eq!(ramp.len(), 5);
+        assert_eq!(ramp[0].name, "bg_blue_0");
+        assert_eq!(ramp[4].name, "bg_blue_4");
+        assert_lab_eq(ramp[0].lab, palette.colors[0].lab);
+        assert_lab_eq(ramp[4].lab, palette.colors[2].lab);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ramp_lab(palette: Palette<3>) -> Result<()> {
+        let ramp = palette.ramp("bg", "fg", 3, Interpolation::Lab)?;
+        assert_lab_eq(ramp[1].lab, Lab::new(63.5, -3.0, 3.5));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ramp_lch_shortest_hue_path() {
+        // Hues of 350 and 10 degrees meet at 0 degrees, not at 180.
+        let from = BaseColor {
+            name: "from".to_string(),
+            lab: Lab::from_color_unclamped(Lch::new(50.0, 40.0, 350.0)),
+        };
+        let to = BaseColor {
+            name: "to".to_string(),
+            lab: Lab::from_color_unclamped(Lch::new(50.0, 40.0, 10.0)),
+        };
+
+        let middle = Lch::from_color_unclamped(from.mix(&to, 0.5, Interpolation::Lch));
+        assert!((middle.chroma - 40.0).abs() < 1e-3);
+        assert!(middle.hue.into_degrees().abs() < 1e-3);
+    }
+
+    #[rstest]
+    fn test_ramp_unknown_color(palette: Palette<3>) {
+        assert!(palette.ramp("bg", "red", 3, Interpolation::Lab).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::palette::DerivedPalette;
+use crate::palette::Palette;
+use crate::serialize::{Deserializable, Format, Serializable};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+impl<const N: usize> Palette<N> {
+    /// Deserializes a base palette from a JSON string.
+    pub fn from_json(json: &str) -> Result<Palette<N>> {
+        Palette::from_str(json, Format::Json)
+    }
+
+    /// Serializes this base palette to a (pretty-printed) JSON string.
+    pub fn to_json(&self) -> Result<String> {
+        self.serialize(Format::Json)
+    }
+}
+
+impl<'a, const N: usize> DerivedPalette<'a, N> {
+    /// Serializes this derived palette to a (pretty-printed) JSON string.
+    pub fn to_json(&self) -> Result<String> {
+        self.serialize(Format::Json)
+    }
+}
+
+pub(crate) fn to_string<T: serde::Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+pub(crate) fn from_str<T: DeserializeOwned>(s: &str) -> Result<T> {
+    Ok(serde_json::from_str(s)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::BaseColor;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    const PALETTE_JSON: &str = r#"{
+  "name": "My Palette",
+  "colors": [
+    {
+      "name": "bg",
+      "lab": {
+        "l": 96.0,
+        "a": 0.0,
+        "b": 13.0
+      }
+    },
+    {
+      "name": "fg",
+      "lab": {
+        "l": 31.0,
+        "a": -6.0,
+        "b": -6.0
+      }
+    }
+  ]
+}"#;
+
+    #[rstest]
+    fn test_json_serialize(palette: Palette<2>) -> Result<()> {
+        let json = palette.to_json()?;
+        assert_eq!(json, PALETTE_JSON);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_json_deserialize(palette: Palette<2>) -> Result<()> {
+        let de_palette = Palette::from_json(PALETTE_JSON)?;
+        assert_eq!(de_palette, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_json_serialize_derived(palette: Palette<2>) -> Result<()> {
+        let json = DerivedPalette::from(&palette).to_json()?;
+        assert!(json.contains(
+            r#"
+      "srgb": {
+        "red": 254,
+        "green": 243,
+        "blue": 218
+      },
+      "srgb_hex": "fef3da"
+"#
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::palette::{some, BaseColor, Palette};
+use crate::serialize::{from_str, Format};
+
+/// A palette file as written, which may extend another palette file instead of
+/// listing all of its colors.
+#[derive(Deserialize)]
+struct PaletteFile {
+    /// The palette's name, which defaults to the extended palette's.
+    #[serde(default, deserialize_with = "some")]
+    name: Option<String>,
+
+    /// The path to the palette file to extend, relative to this file.
+    #[serde(default, deserialize_with = "some")]
+    extends: Option<PathBuf>,
+
+    /// All of the palette's colors or, when extending, the colors to override
+    /// by name.
+    #[serde(default, deserialize_with = "colors")]
+    colors: Vec<BaseColor>,
+}
+
+/// Deserializes a palette file's colors from a list or, as a palette of a
+/// fixed number of colors is serialized in RON, from a tuple.
+fn colors<'de, D>(deserializer: D) -> Result<Vec<BaseColor>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ColorsVisitor;
+
+    impl<'de> Visitor<'de> for ColorsVisitor {
+        type Value = Vec<BaseColor>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a list of colors")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Vec<BaseColor>, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut colors = Vec::new();
+            while let Some(color) = seq.next_element()? {
+                colors.push(color);
+            }
+
+            Ok(colors)
+        }
+    }
+
+    deserializer.deserialize_any(ColorsVisitor)
+}
+
+impl<const N: usize> Palette<N> {
+    /// Loads a palette file, choosing its deserializer by the file's extension,
+    /// or by the given `fallback` format if the extension is not recognized.
+    ///
+    /// A palette file may declare `extends: <path>` (relative to itself) to
+    /// inherit another palette file's colors, and then only list the colors it
+    /// overrides by name:
+    ///
+    /// ```yaml
+    /// extends: selenized_dark.yaml
+    /// name: Selenized black
+    /// colors:
+    /// - name: bg_0
+    ///   hex: "#181818"
+    /// ```
+    ///
+    /// Extended palette files are resolved recursively, and may be in any of
+    /// the enabled formats.
+    pub fn from_path(path: &Path, fallback: Format) -> Result<Palette<N>> {
+        load(path, fallback, &mut Vec::new(), &mut Vec::new())
+    }
+
+    /// Loads a palette file, as [`Palette::from_path`] does, also returning the
+    /// (canonical) paths of the files read: its own, then those of its
+    /// `extends` chain (e.g. to watch them for changes).
+    pub fn from_path_with_files(
+        path: &Path,
+        fallback: Format,
+    ) -> Result<(Palette<N>, Vec<PathBuf>)> {
+        let mut files = Vec::new();
+        let palette = load(path, fallback, &mut Vec::new(), &mut files)?;
+
+        Ok((palette, files))
+    }
+
+    /// Loads a palette from a string in the given format (e.g. read from
+    /// stdin), as [`Palette::from_path`] does a file, resolving its `extends`
+    /// path, if any, relative to the directory `base_dir`.
+    pub fn from_str_in(s: &str, format: Format, base_dir: &Path) -> Result<Palette<N>> {
+        let file: PaletteFile = from_str(s, format)
+            .with_context(|| format!("Could not deserialize {} to palette", format))?;
+
+        resolve(
+            file,
+            "<input>",
+            base_dir,
+            format,
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+    }
+}
+
+/// Loads a palette file, resolving its `extends` chain. `chain` holds the
+/// (canonical) paths of the files currently being loaded, to detect cycles,
+/// and `files` collects those of all the files read.
+fn load<const N: usize>(
+    path: &Path,
+    fallback: Format,
+    chain: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<Palette<N>> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("Could not find palette file {}", path.display()))?;
+    if chain.contains(&canonical) {
+        let cycle: Vec<String> = chain
+            .iter()
+            .chain([&canonical])
+            .map(|path| path.display().to_string())
+            .collect();
+        bail!("Cyclic palette inheritance: {}", cycle.join(" -> "));
+    }
+    files.push(canonical.clone());
+
+    let format = Format::from_path(path).unwrap_or(fallback);
+    let s = fs::read_to_string(path)
+        .with_context(|| format!("Could not read palette file {}", path.display()))?;
+    let file: PaletteFile = from_str(&s, format).with_context(|| {
+        format!(
+            "Could not deserialize {} to palette: {}",
+            format,
+            path.display()
+        )
+    })?;
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    chain.push(canonical);
+    let palette = resolve(file, &path.display().to_string(), dir, format, chain, files);
+    chain.pop();
+
+    palette
+}
+
+/// Resolves a deserialized palette file (named `origin`, for error messages)
+/// into a palette, loading the palette it `extends`, if any, relative to `dir`.
+fn resolve<const N: usize>(
+    file: PaletteFile,
+    origin: &str,
+    dir: &Path,
+    format: Format,
+    chain: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<Palette<N>> {
+    let Some(extends) = file.extends else {
+        let name = file
+            .name
+            .ok_or_else(|| anyhow!("Missing name in palette file {}", origin))?;
+        return Palette::from_vec(name, file.colors)
+            .with_context(|| format!("Invalid palette file {}", origin));
+    };
+
+    let parent_path = dir.join(extends);
+    let mut palette: Palette<N> = load(&parent_path, format, chain, files)?;
+
+    for color in file.colors {
+        let mut matches = palette
+            .colors
+            .iter_mut()
+            .filter(|base| base.name == color.name);
+        let (Some(base), None) = (matches.next(), matches.next()) else {
+            bail!(
+                "Cannot override color {} in palette file {}: it must name exactly one color of {}",
+                color.name,
+                origin,
+                parent_path.display()
+            );
+        };
+        *base = color;
+    }
+    if let Some(name) = file.name {
+        palette.name = name;
+    }
+
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::Serializable;
+    use crate::test_utils::*;
+
+    use rstest::*;
+
+    const BASE_YAML: &str = r#"name: Base
+colors:
+- name: bg
+  lab: {l: 96.0, a: 0.0, b: 13.0}
+- name: fg
+  lab: {l: 31.0, a: -6.0, b: -6.0}
+"#;
+
+    #[rstest]
+    #[cfg_attr(feature = "ron", case::ron(Format::Ron, "gen.ron"))]
+    #[cfg_attr(feature = "yaml", case::yaml(Format::Yaml, "gen.yaml"))]
+    #[cfg_attr(feature = "json", case::json(Format::Json, "gen.json"))]
+    #[cfg_attr(feature = "toml", case::toml(Format::Toml, "gen.toml"))]
+    fn test_load_roundtrip(
+        tmpdir: TempDirFixture,
+        #[case] format: Format,
+        #[case] filename: &str,
+    ) -> Result<()> {
+        let palette = Palette::new(
+            "Base",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        );
+        let path = tmpdir.write_to_file(filename, &palette.serialize(format)?)?;
+
+        assert_eq!(Palette::<2>::from_path(&path, Format::Yaml)?, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_extends(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("base.yaml", BASE_YAML)?;
+        let path = tmpdir.write_to_file(
+            "variant.yaml",
+            "extends: base.yaml\nname: Variant\ncolors:\n- name: fg\n  hex: \"#000000\"\n",
+        )?;
+
+        let palette = Palette::<2>::from_path(&path, Format::Yaml)?;
+        assert_eq!(palette.name, "Variant");
+        assert_eq!(palette.colors[0], BaseColor::new("bg", 96, 0, 13));
+        assert_eq!(palette.colors[1].name, "fg");
+        assert!(palette.colors[1].lab.l.abs() < 1e-3);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_str_extends(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("base.yaml", BASE_YAML)?;
+
+        let palette = Palette::<2>::from_str_in(
+            "extends: base.yaml\nname: Variant\n",
+            Format::Yaml,
+            tmpdir.tmpdir.path(),
+        )?;
+        assert_eq!(palette.name, "Variant");
+        assert_eq!(palette.colors[0], BaseColor::new("bg", 96, 0, 13));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_extends_chain(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("base.yaml", BASE_YAML)?;
+        tmpdir.write_to_file("variant.yaml", "extends: base.yaml\nname: Variant\n")?;
+        fs::create_dir(tmpdir.tmpdir.path().join("nested"))?;
+        let path = tmpdir.write_to_file("nested/leaf.yaml", "extends: ../variant.yaml\n")?;
+
+        let palette = Palette::<2>::from_path(&path, Format::Yaml)?;
+        assert_eq!(palette.name, "Variant");
+        assert_eq!(palette.colors[1], BaseColor::new("fg", 31, -6, -6));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_extends_files(tmpdir: TempDirFixture) -> Result<()> {
+        let base_path = tmpdir.write_to_file("base.yaml", BASE_YAML)?;
+        fs::create_dir(tmpdir.tmpdir.path().join("nested"))?;
+        let path = tmpdir.write_to_file("nested/leaf.yaml", "extends: ../base.yaml\n")?;
+
+        let (palette, files) = Palette::<2>::from_path_with_files(&path, Format::Yaml)?;
+        assert_eq!(palette.name, "Base");
+        assert_eq!(files, vec![path.canonicalize()?, base_path.canonicalize()?]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_extends_cycle(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("a.yaml", "extends: b.yaml\n")?;
+        let path = tmpdir.write_to_file("b.yaml", "extends: a.yaml\n")?;
+
+        let err = Palette::<2>::from_path(&path, Format::Yaml).unwrap_err();
+        assert!(err.to_string().starts_with("Cyclic palette inheritance: "));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_extends_unknown_color(tmpdir: TempDirFixture) -> Result<()> {
+        tmpdir.write_to_file("base.yaml", BASE_YAML)?;
+        let path = tmpdir.write_to_file(
+            "variant.yaml",
+            "extends: base.yaml\ncolors:\n- name: cursor\n  hex: \"#000000\"\n",
+        )?;
+
+        let err = Palette::<2>::from_path(&path, Format::Yaml).unwrap_err();
+        assert!(err.to_string().starts_with("Cannot override color cursor"));
+
+        Ok(())
+    }
+}
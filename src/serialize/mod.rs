@@ -1,9 +1,189 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
 
-/// A trait for an object that can serialize and deserialize to and from a utf-8 string.
+use crate::diff::PaletteDiff;
+use crate::palette::{DerivedPalette, OwnedDerivedPalette, Palette};
+
+/// A serialization format, available when its cargo feature is enabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "ron")]
+    Ron,
+}
+
+impl Format {
+    /// Guesses the format of a file from its extension, if recognized.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    /// Parses a format from its (lowercase) name or file extension.
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Ok(Format::Yaml),
+            #[cfg(feature = "json")]
+            "json" => Ok(Format::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Ok(Format::Toml),
+            #[cfg(feature = "ron")]
+            "ron" => Ok(Format::Ron),
+            _ => Err(anyhow!(
+                "Unknown or disabled serialization format: \"{}\"",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            #[cfg(feature = "yaml")]
+            Format::Yaml => "YAML",
+            #[cfg(feature = "json")]
+            Format::Json => "JSON",
+            #[cfg(feature = "toml")]
+            Format::Toml => "TOML",
+            #[cfg(feature = "ron")]
+            Format::Ron => "RON",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A trait for an object that can serialize itself to a utf-8 string, in any
+/// of the enabled formats.
 pub trait Serializable {
-    fn serialize(&self) -> Result<String>;
+    fn serialize(&self, format: Format) -> Result<String>;
+}
+
+/// A trait for an object that can deserialize itself from utf-8 data, in any
+/// of the enabled formats.
+pub trait Deserializable: Sized {
+    fn from_str(s: &str, format: Format) -> Result<Self>;
+
+    fn from_reader<R: Read>(mut reader: R, format: Format) -> Result<Self> {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .with_context(|| format!("Could not read {} data", format))?;
+        Self::from_str(&s, format)
+    }
+}
+
+impl<const N: usize> Serializable for Palette<N> {
+    fn serialize(&self, format: Format) -> Result<String> {
+        to_string(self, format)
+            .with_context(|| format!("Could not serialize palette to {}:\n{:?}", format, self))
+    }
+}
+
+impl<'a, const N: usize> Serializable for DerivedPalette<'a, N> {
+    fn serialize(&self, format: Format) -> Result<String> {
+        to_string(self, format).with_context(|| {
+            format!(
+                "Could not serialize derived palette to {}:\n{:?}",
+                format, self
+            )
+        })
+    }
 }
 
+impl<const N: usize> Deserializable for Palette<N> {
+    fn from_str(s: &str, format: Format) -> Result<Self> {
+        from_str(s, format)
+            .with_context(|| format!("Could not deserialize {} to palette:\n{}", format, s))
+    }
+}
+
+impl<const N: usize> Serializable for OwnedDerivedPalette<N> {
+    fn serialize(&self, format: Format) -> Result<String> {
+        to_string(self, format).with_context(|| {
+            format!(
+                "Could not serialize derived palette to {}:\n{:?}",
+                format, self
+            )
+        })
+    }
+}
+
+impl<const N: usize> Deserializable for OwnedDerivedPalette<N> {
+    fn from_str(s: &str, format: Format) -> Result<Self> {
+        from_str(s, format).with_context(|| {
+            format!(
+                "Could not deserialize {} to derived palette:\n{}",
+                format, s
+            )
+        })
+    }
+}
+
+impl Serializable for PaletteDiff {
+    fn serialize(&self, format: Format) -> Result<String> {
+        to_string(self, format).with_context(|| {
+            format!(
+                "Could not serialize palette diff to {}:\n{:?}",
+                format, self
+            )
+        })
+    }
+}
+
+/// Serializes any serde value with the given format's serializer.
+fn to_string<T: Serialize>(value: &T, format: Format) -> Result<String> {
+    match format {
+        #[cfg(feature = "yaml")]
+        Format::Yaml => yaml::to_string(value),
+        #[cfg(feature = "json")]
+        Format::Json => json::to_string(value),
+        #[cfg(feature = "toml")]
+        Format::Toml => self::toml::to_string(value),
+        #[cfg(feature = "ron")]
+        Format::Ron => self::ron::to_string(value),
+    }
+}
+
+/// Deserializes any serde value with the given format's deserializer.
+pub(crate) fn from_str<T: DeserializeOwned>(s: &str, format: Format) -> Result<T> {
+    match format {
+        #[cfg(feature = "yaml")]
+        Format::Yaml => yaml::from_str(s),
+        #[cfg(feature = "json")]
+        Format::Json => json::from_str(s),
+        #[cfg(feature = "toml")]
+        Format::Toml => self::toml::from_str(s),
+        #[cfg(feature = "ron")]
+        Format::Ron => self::ron::from_str(s),
+    }
+}
+
+#[cfg(feature = "json")]
+pub mod json;
+
+mod load;
+
+#[cfg(feature = "ron")]
+pub mod ron;
+
+#[cfg(feature = "toml")]
+pub mod toml;
+
 #[cfg(feature = "yaml")]
 pub mod yaml;
@@ -0,0 +1,99 @@
+use crate::palette::DerivedPalette;
+use crate::palette::Palette;
+use crate::serialize::{Deserializable, Format, Serializable};
+
+use anyhow::Result;
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+
+impl<const N: usize> Palette<N> {
+    /// Deserializes a base palette from a RON string.
+    ///
+    /// Since a palette has a fixed number of colors, they are written as a RON
+    /// tuple (`colors: ((...), (...))`) rather than a list.
+    pub fn from_ron(ron: &str) -> Result<Palette<N>> {
+        Palette::from_str(ron, Format::Ron)
+    }
+
+    /// Serializes this base palette to a (pretty-printed) RON string.
+    pub fn to_ron(&self) -> Result<String> {
+        self.serialize(Format::Ron)
+    }
+}
+
+impl<'a, const N: usize> DerivedPalette<'a, N> {
+    /// Serializes this derived palette to a (pretty-printed) RON string.
+    pub fn to_ron(&self) -> Result<String> {
+        self.serialize(Format::Ron)
+    }
+}
+
+pub(crate) fn to_string<T: serde::Serialize>(value: &T) -> Result<String> {
+    Ok(ron::ser::to_string_pretty(value, PrettyConfig::default())?)
+}
+
+pub(crate) fn from_str<T: DeserializeOwned>(s: &str) -> Result<T> {
+    Ok(ron::from_str(s)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::BaseColor;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    const PALETTE_RON: &str = r#"(
+    name: "My Palette",
+    colors: ((
+        name: "bg",
+        lab: (
+            l: 96.0,
+            a: 0.0,
+            b: 13.0,
+        ),
+    ), (
+        name: "fg",
+        lab: (
+            l: 31.0,
+            a: -6.0,
+            b: -6.0,
+        ),
+    )),
+)"#;
+
+    #[rstest]
+    fn test_ron_serialize(palette: Palette<2>) -> Result<()> {
+        let ron = palette.to_ron()?;
+        assert_eq!(ron, PALETTE_RON);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ron_deserialize(palette: Palette<2>) -> Result<()> {
+        let de_palette = Palette::from_ron(PALETTE_RON)?;
+        assert_eq!(de_palette, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_ron_serialize_derived(palette: Palette<2>) -> Result<()> {
+        let ron = DerivedPalette::from(&palette).to_ron()?;
+        assert!(ron.contains("srgb_hex: \"fef3da\",\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::palette::DerivedPalette;
+use crate::palette::Palette;
+use crate::serialize::{Deserializable, Format, Serializable};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+impl<const N: usize> Palette<N> {
+    /// Deserializes a base palette from a TOML string.
+    pub fn from_toml(toml: &str) -> Result<Palette<N>> {
+        Palette::from_str(toml, Format::Toml)
+    }
+
+    /// Serializes this base palette to a TOML string.
+    pub fn to_toml(&self) -> Result<String> {
+        self.serialize(Format::Toml)
+    }
+}
+
+impl<'a, const N: usize> DerivedPalette<'a, N> {
+    /// Serializes this derived palette to a TOML string.
+    pub fn to_toml(&self) -> Result<String> {
+        self.serialize(Format::Toml)
+    }
+}
+
+pub(crate) fn to_string<T: serde::Serialize>(value: &T) -> Result<String> {
+    Ok(toml::to_string(value)?)
+}
+
+pub(crate) fn from_str<T: DeserializeOwned>(s: &str) -> Result<T> {
+    Ok(toml::from_str(s)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::BaseColor;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<2> {
+        Palette::new(
+            "My Palette",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        )
+    }
+
+    const PALETTE_TOML: &str = r#"name = "My Palette"
+
+[[colors]]
+name = "bg"
+
+[colors.lab]
+l = 96.0
+a = 0.0
+b = 13.0
+
+[[colors]]
+name = "fg"
+
+[colors.lab]
+l = 31.0
+a = -6.0
+b = -6.0
+"#;
+
+    #[rstest]
+    fn test_toml_serialize(palette: Palette<2>) -> Result<()> {
+        let toml = palette.to_toml()?;
+        assert_eq!(toml, PALETTE_TOML);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_toml_deserialize(palette: Palette<2>) -> Result<()> {
+        let de_palette = Palette::from_toml(PALETTE_TOML)?;
+        assert_eq!(de_palette, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_toml_serialize_derived(palette: Palette<2>) -> Result<()> {
+        let toml = DerivedPalette::from(&palette).to_toml()?;
+        assert!(toml.contains("srgb_hex = \"fef3da\"\n"));
+
+        Ok(())
+    }
+}
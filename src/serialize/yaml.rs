@@ -1,35 +1,33 @@
-use crate::palette::DerivedPalette;
 use crate::palette::Palette;
-use crate::serialize::Serializable;
+use crate::serialize::{Deserializable, Format};
 
-use anyhow::{Context, Result};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
 
 impl<const N: usize> Palette<N> {
     /// Deserializes a base palette from a YAML string.
+    ///
+    /// Colors may be given by `hex`, `srgb` or `hsl` instead of `lab` (see
+    /// [`BaseColor`](crate::BaseColor)). Serializing the palette back writes
+    /// them normalized to CIE L*a*b*.
     pub fn from_yaml(yaml: &str) -> Result<Palette<N>> {
-        serde_yaml::from_str(yaml)
-            .with_context(|| format!("Could not deserialize YAML to palette:\n{}", yaml))
+        Palette::from_str(yaml, Format::Yaml)
     }
 }
 
-impl<const N: usize> Serializable for Palette<N> {
-    fn serialize(&self) -> Result<String> {
-        serde_yaml::to_string(self)
-            .with_context(|| format!("Could not serialize palette to YAML:\n{:?}", self))
-    }
+pub(crate) fn to_string<T: serde::Serialize>(value: &T) -> Result<String> {
+    Ok(serde_yaml::to_string(value)?)
 }
 
-impl<'a, const N: usize> Serializable for DerivedPalette<'a, N> {
-    fn serialize(&self) -> Result<String> {
-        serde_yaml::to_string(self)
-            .with_context(|| format!("Could not serialize derived palette to YAML:\n{:?}", self))
-    }
+pub(crate) fn from_str<T: DeserializeOwned>(s: &str) -> Result<T> {
+    Ok(serde_yaml::from_str(s)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::palette::{Base16Palette, BaseColor};
+    use crate::palette::{Base16OwnedDerivedPalette, Base16Palette, BaseColor, DerivedPalette};
+    use crate::serialize::Serializable;
 
     use rstest::*;
 
@@ -145,7 +143,7 @@ colors:
 
     #[rstest]
     fn test_yaml_serialize(palette: Base16Palette) -> Result<()> {
-        let yaml = palette.serialize()?;
+        let yaml = palette.serialize(Format::Yaml)?;
         assert_eq!(yaml, PALETTE_YAML);
 
         Ok(())
@@ -159,4 +157,96 @@ colors:
 
         Ok(())
     }
+
+    #[rstest]
+    fn test_yaml_deserialize_from_reader(palette: Base16Palette) -> Result<()> {
+        let de_palette = Palette::from_reader(PALETTE_YAML.as_bytes(), Format::Yaml)?;
+        assert_eq!(de_palette, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_yaml_deserialize_color_inputs() -> Result<()> {
+        let yaml = r##"
+name: Inputs
+colors:
+- name: lab
+  lab: {l: 96.0, a: 0.0, b: 13.0}
+- name: hex
+  hex: "#384c52"
+- name: srgb
+  srgb: {red: 56, green: 76, blue: 82}
+- name: hsl
+  hsl: {hue: 0.0, saturation: 1.0, lightness: 0.5}
+"##;
+        let palette = Palette::<4>::from_yaml(yaml)?;
+        let derived = DerivedPalette::from(&palette);
+        let hexes = derived
+            .colors
+            .each_ref()
+            .map(|color| color.srgb_hex.as_str());
+        assert_eq!(hexes, ["fef3da", "384c52", "384c52", "ff0000"]);
+
+        // Normalized back to L*a*b*.
+        let normalized = palette.serialize(Format::Yaml)?;
+        assert!(!normalized.contains("hex:"));
+        assert_eq!(Palette::<4>::from_yaml(&normalized)?, palette);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::none("name: c")]
+    #[case::both("name: c\n  hex: fef3da\n  lab: {l: 96.0, a: 0.0, b: 13.0}")]
+    #[case::bad_hex("name: c\n  hex: fef3d")]
+    fn test_yaml_deserialize_color_inputs_invalid(#[case] color: &str) {
+        let yaml = format!("name: Inputs\ncolors:\n- {}\n", color);
+        assert!(Palette::<1>::from_yaml(&yaml).is_err());
+    }
+
+    #[rstest]
+    fn test_yaml_derived_round_trip(palette: Base16Palette) -> Result<()> {
+        let derived = DerivedPalette::from(&palette);
+        let yaml = derived.serialize(Format::Yaml)?;
+
+        let de_derived = Base16OwnedDerivedPalette::from_str(&yaml, Format::Yaml)?;
+        assert_eq!(de_derived, (&derived).into());
+        assert_eq!(de_derived.serialize(Format::Yaml)?, yaml);
+        assert_eq!(de_derived.to_palette(), palette);
+        assert!(de_derived.verify().is_empty());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_yaml_derived_verify_mismatch(palette: Base16Palette) -> Result<()> {
+        let yaml = DerivedPalette::from(&palette).serialize(Format::Yaml)?;
+        // Hand-edit the hex of `fg_1` (base05) without touching its L*a*b*.
+        let tampered = yaml.replace("srgb_hex: 384c52", "srgb_hex: '#000000'");
+
+        let de_derived = Base16OwnedDerivedPalette::from_str(&tampered, Format::Yaml)?;
+        let mismatches = de_derived.verify();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 5);
+        assert_eq!(mismatches[0].name, "fg_1");
+        assert_eq!(mismatches[0].stored_hex, "000000");
+        assert_eq!(mismatches[0].derived_hex, "384c52");
+        assert_eq!(
+            mismatches[0].to_string(),
+            "base05 (fg_1): stored sRGB #000000 does not match #384c52 derived from L*a*b*"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_format_from_path() {
+        use std::path::Path;
+
+        assert_eq!(Format::from_path(Path::new("a.yaml")), Some(Format::Yaml));
+        assert_eq!(Format::from_path(Path::new("a.yml")), Some(Format::Yaml));
+        assert_eq!(Format::from_path(Path::new("a.gpl")), None);
+        assert_eq!(Format::from_path(Path::new("a")), None);
+    }
 }
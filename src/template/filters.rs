@@ -0,0 +1,115 @@
+use liquid_core::model::to_value;
+use liquid_core::{
+    Display_filter, Error, Expression, Filter, FilterParameters, FilterReflection,
+    FromFilterParameters, ParseFilter, Result, Runtime, Value, ValueView,
+};
+use palette::lab::Lab;
+
+use crate::palette::{BaseColor, DerivedColor};
+use crate::ramp::Interpolation;
+
+#[derive(Debug, FilterParameters)]
+struct RampArgs {
+    #[parameter(description = "The name of the color to start from.", arg_type = "str")]
+    from: Expression,
+
+    #[parameter(description = "The name of the color to end at.", arg_type = "str")]
+    to: Expression,
+
+    #[parameter(
+        description = "The number of colors in the ramp, ends included.",
+        arg_type = "integer"
+    )]
+    steps: Expression,
+
+    #[parameter(
+        description = "The interpolation colorspace: lab (default), lch or oklab.",
+        arg_type = "str"
+    )]
+    interpolation: Option<Expression>,
+}
+
+/// A Liquid filter which turns a (derived) palette into a ramp of derived
+/// colors between two of its colors, given by name:
+///
+/// ```liquid
+/// {% assign shades = palette | ramp: "bg_0", "blue", 8, "oklab" %}
+/// {% for color in shades %}#{{ color.srgb_hex }} {% endfor %}
+/// ```
+///
+/// Ramp colors have the same shape as the palette's colors. Since Liquid's
+/// `for` tag does not take filters, a ramp is `assign`ed before iterating over
+/// it.
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "ramp",
+    description = "Interpolates a ramp of colors between two of a palette's colors.",
+    parameters(RampArgs),
+    parsed(RampFilter)
+)]
+pub struct Ramp;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "ramp"]
+struct RampFilter {
+    #[parameters]
+    args: RampArgs,
+}
+
+impl Filter for RampFilter {
+    fn evaluate(&self, input: &dyn ValueView, runtime: &dyn Runtime) -> Result<Value> {
+        let args = self.args.evaluate(runtime)?;
+
+        let steps = usize::try_from(args.steps)
+            .map_err(|_| Error::with_msg("Invalid argument").context("argument", "steps"))?;
+        let interpolation = match args.interpolation {
+            Some(interpolation) => interpolation
+                .parse::<Interpolation>()
+                .map_err(|err| Error::with_msg(err.to_string()))?,
+            None => Interpolation::default(),
+        };
+
+        let from = find_color(input, &args.from)?;
+        let to = find_color(input, &args.to)?;
+
+        let colors: Vec<Value> = from
+            .ramp(&to, steps, interpolation)
+            .iter()
+            .map(|color| to_value(&DerivedColor::from(color)))
+            .collect::<Result<_>>()?;
+
+        Ok(Value::Array(colors))
+    }
+}
+
+/// Finds a color by name in a (derived) palette value, or in an array of
+/// (derived) colors.
+fn find_color(input: &dyn ValueView, name: &str) -> Result<BaseColor> {
+    let colors = match input.as_object() {
+        Some(palette) => palette.get("colors"),
+        None => Some(input),
+    }
+    .and_then(|colors| colors.as_array())
+    .ok_or_else(|| Error::with_msg("Invalid input").context("cause", "Palette expected"))?;
+
+    let base = colors
+        .values()
+        .filter_map(|color| color.as_object()?.get("base")?.as_object())
+        .find(|base| base.get("name").is_some_and(|n| n.to_kstr() == name))
+        .ok_or_else(|| Error::with_msg(format!("No color named {} in palette", name)))?;
+
+    let component = |key: &str| {
+        base.get("lab")
+            .and_then(|lab| lab.as_object()?.get(key)?.as_scalar()?.to_float())
+            .ok_or_else(|| Error::with_msg(format!("Invalid L*a*b* values for color {}", name)))
+    };
+
+    Ok(BaseColor {
+        name: name.to_string(),
+        lab: Lab::new(
+            component("l")? as f32,
+            component("a")? as f32,
+            component("b")? as f32,
+        ),
+    })
+}
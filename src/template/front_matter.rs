@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+
+use crate::serialize::yaml;
+use crate::variant::Polarity;
+
+/// The delimiter line that opens and closes a template's front-matter.
+const DELIMITER: &str = "---";
+
+/// The fields of a front-matter, one of which a `---` block must declare to
+/// be one.
+const FIELDS: [&str; 4] = ["output", "mode", "variant", "palette_size"];
+
+/// A template's metadata, declared in YAML front-matter at its very top:
+///
+/// ```liquid
+/// ---
+/// output: "{{ palette.slug }}/kitty.conf"
+/// mode: "0644"
+/// variant: dark
+/// palette_size: 16
+/// ---
+/// background #{{ bg_0 }}
+/// ```
+#[derive(Deserialize, Default, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FrontMatter {
+    /// The output path pattern of the rendered file, a Liquid template (see
+    /// [`Manifest`](crate::Manifest)).
+    #[serde(default)]
+    pub output: Option<String>,
+
+    /// The (unix) permissions of the rendered file, given in octal (e.g.
+    /// `"0755"`).
+    #[serde(default, deserialize_with = "octal")]
+    pub mode: Option<u32>,
+
+    /// The only palette polarity (`light` or `dark`) this template applies to.
+    #[serde(default)]
+    pub variant: Option<Polarity>,
+
+    /// The number of colors a palette must have to render this template.
+    #[serde(default)]
+    pub palette_size: Option<usize>,
+}
+
+impl FrontMatter {
+    /// Splits a template's source into its front-matter, if any, and the rest
+    /// of the template.
+    ///
+    /// Front-matter must open on the template's first line, be closed by the
+    /// next `---` line, and declare at least one of its fields (or none at
+    /// all). Any other leading `---` line, e.g. a YAML document marker in a
+    /// template for a YAML file, is left in the template; to keep a block that
+    /// would be front-matter, write its first line as `{% raw %}---{% endraw %}`.
+    pub fn split(source: &str) -> Result<(FrontMatter, &str)> {
+        let no_front_matter = Ok((FrontMatter::default(), source));
+        let Some(rest) = strip_delimiter_line(source) else {
+            return no_front_matter;
+        };
+
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == DELIMITER {
+                let yaml = &rest[..offset];
+                let front_matter = if yaml.trim().is_empty() {
+                    FrontMatter::default()
+                } else if declares_fields(yaml) {
+                    yaml::from_str(yaml).context("Could not deserialize YAML front-matter")?
+                } else {
+                    return no_front_matter;
+                };
+                return Ok((front_matter, &rest[offset + line.len()..]));
+            }
+            offset += line.len();
+        }
+
+        no_front_matter
+    }
+}
+
+/// Whether a `---` block is a YAML mapping that declares any front-matter
+/// field.
+fn declares_fields(yaml: &str) -> bool {
+    yaml::from_str::<HashMap<String, IgnoredAny>>(yaml)
+        .is_ok_and(|fields| fields.keys().any(|key| FIELDS.contains(&key.as_str())))
+}
+
+/// Strips the opening delimiter line from a template's source, if it has one.
+fn strip_delimiter_line(source: &str) -> Option<&str> {
+    let rest = source.strip_prefix(DELIMITER)?;
+    rest.strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))
+}
+
+/// Deserializes a file mode from an octal string (e.g. `"0644"`), or from an
+/// integer written with octal digits (e.g. `644`).
+fn octal<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Mode {
+        String(String),
+        Integer(u32),
+    }
+
+    let digits = match Mode::deserialize(deserializer)? {
+        Mode::String(s) => s,
+        Mode::Integer(i) => i.to_string(),
+    };
+    let digits = digits.trim_start_matches("0o");
+
+    u32::from_str_radix(digits, 8)
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("Invalid octal file mode: {}", digits)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest]
+    fn test_split() -> Result<()> {
+        let source = "---\noutput: \"{{ palette.slug }}.conf\"\nmode: \"0755\"\nvariant: dark\npalette_size: 16\n---\nbg: {{ bg_0 }}\n";
+
+        let (front_matter, template) = FrontMatter::split(source)?;
+        assert_eq!(
+            front_matter,
+            FrontMatter {
+                output: Some("{{ palette.slug }}.conf".to_string()),
+                mode: Some(0o755),
+                variant: Some(Polarity::Dark),
+                palette_size: Some(16),
+            }
+        );
+        assert_eq!(template, "bg: {{ bg_0 }}\n");
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::none("bg: {{ bg_0 }}\n")]
+    #[case::horizontal_rule("----\n")]
+    #[case::not_first_line("\n---\nmode: 644\n---\n")]
+    #[case::unclosed("---\nmode: 644\n")]
+    #[case::yaml_document("---\nprimary:\n  background: \"#{{ bg_0 }}\"\n")]
+    #[case::yaml_documents("---\nbackground: 1\n---\nforeground: 2\n")]
+    #[case::escaped("{% raw %}---{% endraw %}\nmode: 644\n---\n")]
+    fn test_split_no_front_matter(#[case] source: &str) -> Result<()> {
+        let (front_matter, template) = FrontMatter::split(source)?;
+        assert_eq!(front_matter, FrontMatter::default());
+        assert_eq!(template, source);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::unknown_field("---\nmode: 644\nmodes: 644\n---\n")]
+    #[case::bad_mode("---\nmode: \"0999\"\n---\n")]
+    fn test_split_invalid(#[case] source: &str) {
+        assert!(FrontMatter::split(source).is_err());
+    }
+
+    #[rstest]
+    fn test_split_empty() -> Result<()> {
+        let (front_matter, template) = FrontMatter::split("---\n---\nbg\n")?;
+        assert_eq!(front_matter, FrontMatter::default());
+        assert_eq!(template, "bg\n");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_split_integer_mode() -> Result<()> {
+        let (front_matter, template) = FrontMatter::split("---\r\nmode: 644\r\n---\r\n")?;
+        assert_eq!(front_matter.mode, Some(0o644));
+        assert_eq!(template, "");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1154 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::palette::{base16_role, Palette};
+use crate::template::strict::{did_you_mean, undefined_hint, RUNTIME_NAMES};
+use crate::template::{PartialFiles, RenderOptions};
+
+/// The names of the palette variables injected into every template.
+const PALETTE_NAMES: [&str; 3] = ["palette", "light", "dark"];
+
+/// Liquid's literal keywords.
+const LITERAL_NAMES: [&str; 6] = ["empty", "blank", "nil", "null", "true", "false"];
+
+/// How well a template (and its partials) covers a palette: the palette
+/// colors it never references, and the names it references that do not exist.
+#[derive(Debug, Default, PartialEq)]
+pub struct LintReport {
+    /// The palette colors never referenced, in palette order.
+    pub unreferenced: Vec<UnreferencedColor>,
+
+    /// The references to names that do not exist (the first one of each name
+    /// in each file).
+    pub unknown: Vec<UnknownReference>,
+}
+
+impl LintReport {
+    /// Whether the template references every palette color, and only names
+    /// that exist.
+    pub fn is_clean(&self) -> bool {
+        self.unreferenced.is_empty() && self.unknown.is_empty()
+    }
+}
+
+/// A palette color that a template never references.
+#[derive(Debug, PartialEq)]
+pub struct UnreferencedColor {
+    /// The index of the color in its palette (i.e. its Base16 role).
+    pub index: usize,
+
+    /// The name of the color.
+    pub name: String,
+}
+
+impl fmt::Display for UnreferencedColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): never referenced",
+            base16_role(self.index),
+            self.name
+        )
+    }
+}
+
+/// What kind of name a template references.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferenceKind {
+    /// A variable, e.g. `{{ bg_0 }}`.
+    Variable,
+    /// A palette color, e.g. `palette.colors[16]` or `ramp: "bg_0", "pink"`.
+    Color,
+    /// A template partial, e.g. `{% render "header" %}`.
+    Partial,
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ReferenceKind::Variable => "variable",
+            ReferenceKind::Color => "color",
+            ReferenceKind::Partial => "partial",
+        })
+    }
+}
+
+/// A template's reference to a name that does not exist.
+#[derive(Debug, PartialEq)]
+pub struct UnknownReference {
+    /// What kind of name is referenced.
+    pub kind: ReferenceKind,
+
+    /// The name referenced, as written in the template.
+    pub name: String,
+
+    /// The path to the template (or partial) file referencing it.
+    pub path: PathBuf,
+
+    /// The 1-based line and column of the (first) reference in the file.
+    pub line: usize,
+    pub column: usize,
+
+    /// A hint to fix the reference, e.g. the closest names that exist.
+    hint: String,
+}
+
+impl fmt::Display for UnknownReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: unknown {} \"{}\"{}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.kind,
+            self.name,
+            self.hint
+        )
+    }
+}
+
+/// A Liquid template (or partial) source to lint.
+pub(crate) struct SourceFile<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) source: &'a str,
+
+    /// The line number, in the file, that `source` starts on.
+    pub(crate) first_line: usize,
+}
+
+impl SourceFile<'_> {
+    /// Returns the 1-based line and column of a byte offset in the source.
+    fn location(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        (
+            before.matches('\n').count() + self.first_line,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    /// Finds the first reference to a variable in the source, as its line and
+    /// column, ignoring text outside of `{{ }}` and `{% %}` tags, string
+    /// literals and lookups of the same name as an attribute (e.g.
+    /// `palette.name`).
+    pub(crate) fn locate(&self, name: &str) -> Option<(usize, usize)> {
+        tags(self.source)
+            .into_iter()
+            .find_map(|(offset, content, _)| {
+                let tokens = tokenize(content, offset);
+                tokens
+                    .iter()
+                    .enumerate()
+                    .find(|&(index, token)| {
+                        token.is_word(name) && (index == 0 || !tokens[index - 1].is_punct("."))
+                    })
+                    .map(|(_, token)| self.location(token.offset))
+            })
+    }
+}
+
+/// Lints a template against a palette it would be rendered with, walking the
+/// `{{ }}` and `{% %}` tags of its source and of the partials it includes or
+/// renders.
+///
+/// Liquid does not expose its parsed templates, so references are found by
+/// tokenizing the tags' expressions: variable paths, `ramp` and `where`
+/// filters' color names, and `include`/`render` partial names. Palette colors
+/// are referenced by unrolled names, by index (e.g. `palette.colors[3]` or
+/// `.first`), or all at once by iterating over or indexing `colors`
+/// dynamically.
+pub(crate) fn lint<const N: usize>(
+    file: &SourceFile,
+    partials: &PartialFiles,
+    palette: &Palette<N>,
+    options: &RenderOptions,
+) -> LintReport {
+    let mut globals: Vec<&str> = PALETTE_NAMES.to_vec();
+    globals.extend(["palettes", "palette_list"]);
+    if options.unroll_colors_hex {
+        globals.extend(palette.colors.iter().map(|color| color.name.as_str()));
+    }
+    globals.extend(options.variables.keys().map(|name| name.as_str()));
+
+    let mut linter = Linter {
+        palette,
+        partials,
+        globals,
+        unrolled: options.unroll_colors_hex,
+        referenced: [false; N],
+        unknown: Vec::new(),
+        stack: Vec::new(),
+    };
+
+    let mut scope = Scope {
+        globals: true,
+        ..Default::default()
+    };
+    linter.walk(file, &mut scope);
+    linter.check_references(scope);
+
+    LintReport {
+        unreferenced: (0..N)
+            .filter(|&index| !linter.referenced[index])
+            .map(|index| UnreferencedColor {
+                index,
+                name: palette.colors[index].name.clone(),
+            })
+            .collect(),
+        unknown: linter.unknown,
+    }
+}
+
+/// What a variable path denotes, as far as palette colors go.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Denotes {
+    /// The `palettes` object, keyed by slug.
+    PalettesBySlug,
+    /// The `palette_list` list.
+    Palettes,
+    Palette,
+    Colors,
+    Color(usize),
+    Other,
+}
+
+impl Denotes {
+    fn is_palette(self) -> bool {
+        self != Denotes::Other
+    }
+}
+
+/// A segment of a variable path, after its root name.
+enum Segment {
+    Name(String),
+    Index(i64),
+    Dynamic,
+}
+
+/// The variables defined in a template, or in a `{% render %}`ed partial
+/// (which does not see the template's).
+#[derive(Default)]
+struct Scope {
+    /// Whether the template's globals (e.g. `palette`) are defined.
+    globals: bool,
+
+    /// The variables assigned, captured, iterated or passed to a partial.
+    locals: HashSet<String>,
+
+    /// The local variables that denote (part of) a palette.
+    aliases: HashMap<String, Denotes>,
+
+    /// The variables referenced, with the file and offset of their reference,
+    /// to check once the whole scope is walked.
+    references: Vec<(String, PathBuf, (usize, usize))>,
+}
+
+impl Scope {
+    fn bind(&mut self, name: &str, denotes: Denotes) {
+        if denotes.is_palette() {
+            self.locals.remove(name);
+            self.aliases.insert(name.to_string(), denotes);
+        } else {
+            self.aliases.remove(name);
+            self.locals.insert(name.to_string());
+        }
+    }
+}
+
+struct Linter<'a, const N: usize> {
+    palette: &'a Palette<N>,
+    partials: &'a PartialFiles,
+    globals: Vec<&'a str>,
+    unrolled: bool,
+    referenced: [bool; N],
+    unknown: Vec<UnknownReference>,
+
+    /// The names of the partials being walked, to not recurse infinitely.
+    stack: Vec<String>,
+}
+
+impl<const N: usize> Linter<'_, N> {
+    /// Walks every tag of a template (or partial) source.
+    fn walk(&mut self, file: &SourceFile, scope: &mut Scope) {
+        for (offset, content, is_output) in tags(file.source) {
+            let tokens = tokenize(content, offset);
+            if is_output {
+                let denotes = self.expression(&tokens, file, scope);
+                self.use_plainly(denotes);
+            } else {
+                self.tag(content, &tokens, file, scope);
+            }
+        }
+    }
+
+    /// Walks a `{% %}` tag's tokens.
+    fn tag(&mut self, content: &str, tokens: &[Token], file: &SourceFile, scope: &mut Scope) {
+        let Some(name) = tokens.first().filter(|token| token.kind == Kind::Ident) else {
+            return;
+        };
+        let args = &tokens[1..];
+
+        match name.text {
+            "liquid" => {
+                // Each line of a `{% liquid %}` tag is a tag of its own.
+                let mut offset = tokens[0].offset - content.find("liquid").unwrap_or(0);
+                for line in content.split_inclusive('\n') {
+                    let line_tokens = tokenize(line, offset);
+                    if line_tokens
+                        .first()
+                        .is_some_and(|token| !token.is_word("liquid"))
+                    {
+                        self.tag(line, &line_tokens, file, scope);
+                    }
+                    offset += line.len();
+                }
+            }
+            "assign" => {
+                if let [variable, equals, value @ ..] = args {
+                    if equals.is_punct("=") {
+                        let denotes = self.expression(value, file, scope);
+                        scope.bind(variable.text, denotes);
+                    }
+                }
+            }
+            "capture" | "increment" | "decrement" => {
+                if let Some(variable) = args.first() {
+                    scope.bind(variable.text, Denotes::Other);
+                }
+            }
+            "for" | "tablerow" => {
+                let [variable, keyword, rest @ ..] = args else {
+                    return;
+                };
+                if !keyword.is_word("in") {
+                    return;
+                }
+                let end = (0..rest.len())
+                    .find(|&index| {
+                        rest[index].is_word("reversed")
+                            || rest.get(index + 1).is_some_and(|next| next.is_punct(":"))
+                    })
+                    .unwrap_or(rest.len());
+
+                let denotes = self.expression(&rest[..end], file, scope);
+                self.expression(&rest[end..], file, scope);
+                match denotes {
+                    Denotes::Palettes => scope.bind(variable.text, Denotes::Palette),
+                    Denotes::Colors => {
+                        self.referenced = [true; N];
+                        scope.bind(variable.text, Denotes::Other);
+                    }
+                    _ => scope.bind(variable.text, Denotes::Other),
+                }
+            }
+            "include" | "render" => self.partial(name.text == "render", args, file, scope),
+            "else" | "break" | "continue" => {}
+            tag if tag.starts_with("end") => {}
+            _ => {
+                let denotes = self.expression(args, file, scope);
+                self.use_plainly(denotes);
+            }
+        }
+    }
+
+    /// Walks an `{% include %}` or `{% render %}` tag's arguments, and the
+    /// partial it names.
+    fn partial(&mut self, render: bool, args: &[Token], file: &SourceFile, scope: &mut Scope) {
+        let Some(name) = args.first() else {
+            return;
+        };
+        if name.kind != Kind::Str {
+            // A partial named by a variable cannot be resolved statically.
+            let denotes = self.expression(args, file, scope);
+            self.use_plainly(denotes);
+            return;
+        }
+
+        let mut bindings = Vec::new();
+        let mut index = 1;
+        while index < args.len() {
+            let token = &args[index];
+            if token.is_word("with") || token.is_word("for") {
+                index += 1;
+                let start = index;
+                while index < args.len() && !args[index].is_word("as") && !args[index].is_punct(",")
+                {
+                    index += 1;
+                }
+                let denotes = match self.expression(&args[start..index], file, scope) {
+                    Denotes::Palettes if token.is_word("for") => Denotes::Palette,
+                    Denotes::Colors if token.is_word("for") => {
+                        self.referenced = [true; N];
+                        Denotes::Other
+                    }
+                    denotes => denotes,
+                };
+                match args.get(index + 1) {
+                    Some(alias) if args[index].is_word("as") => {
+                        bindings.push((alias.text, denotes));
+                        index += 2;
+                    }
+                    _ => self.use_plainly(denotes),
+                }
+            } else if token.kind == Kind::Ident
+                && args.get(index + 1).is_some_and(|next| next.is_punct(":"))
+            {
+                index += 2;
+                let denotes = self.value(args, &mut index, file, scope);
+                bindings.push((token.text, denotes.unwrap_or(Denotes::Other)));
+            } else {
+                index += 1;
+            }
+        }
+
+        let partial = self.partials.files.get_key_value(name.text).or_else(|| {
+            render
+                .then(|| {
+                    self.partials
+                        .files
+                        .get_key_value(&format!("{}.liquid", name.text))
+                })
+                .flatten()
+        });
+        let Some((partial_name, (path, source))) = partial else {
+            let names: Vec<&str> = self.partials.names().collect();
+            let hint = did_you_mean(name.text, &names);
+            self.report(ReferenceKind::Partial, name.text, file, name.offset, hint);
+            return;
+        };
+        if self.stack.contains(partial_name) {
+            return;
+        }
+
+        let partial_file = SourceFile {
+            path,
+            source,
+            first_line: 1,
+        };
+        self.stack.push(partial_name.clone());
+        if render {
+            let mut partial_scope = Scope::default();
+            for (name, denotes) in bindings {
+                partial_scope.bind(name, denotes);
+            }
+            self.walk(&partial_file, &mut partial_scope);
+            self.check_references(partial_scope);
+        } else {
+            for (name, denotes) in bindings {
+                scope.bind(name, denotes);
+            }
+            self.walk(&partial_file, scope);
+        }
+        self.stack.pop();
+    }
+
+    /// Walks an expression: values, operators and filters. Returns what it
+    /// denotes if it is a single variable path, for the caller to use.
+    fn expression(&mut self, tokens: &[Token], file: &SourceFile, scope: &mut Scope) -> Denotes {
+        let mut chain = tokens.split(|token| token.is_punct("|"));
+        let head = chain.next().unwrap_or_default();
+
+        let mut index = 0;
+        let mut values = Vec::new();
+        while index < head.len() {
+            if let Some(denotes) = self.value(head, &mut index, file, scope) {
+                values.push((denotes, index == head.len() && values.is_empty()));
+            }
+        }
+        let single = match values.as_slice() {
+            [(denotes, true)] => Some(*denotes),
+            _ => None,
+        };
+
+        let mut filtered_by_name = false;
+        let mut has_filters = false;
+        for filter in chain {
+            has_filters = true;
+            let Some((name, args)) = filter.split_first() else {
+                continue;
+            };
+            let args = match args.split_first() {
+                Some((colon, args)) if colon.is_punct(":") => args,
+                _ => args,
+            };
+            let strings: Vec<&Token> = args
+                .iter()
+                .filter(|token| token.kind == Kind::Str)
+                .collect();
+            match (name.text, strings.as_slice()) {
+                ("ramp", _) => {
+                    for color in strings.iter().take(2) {
+                        self.color_name(color, file);
+                    }
+                    filtered_by_name = true;
+                }
+                ("where", [key, color, ..]) if key.text == "name" => {
+                    self.color_name(color, file);
+                    filtered_by_name = true;
+                }
+                _ => {}
+            }
+
+            let mut index = 0;
+            while index < args.len() {
+                let denotes = self.value(args, &mut index, file, scope);
+                self.use_plainly(denotes.unwrap_or(Denotes::Other));
+            }
+        }
+
+        match single {
+            Some(denotes) if !has_filters => denotes,
+            Some(_) if filtered_by_name => Denotes::Other,
+            _ => {
+                for (denotes, _) in values {
+                    self.use_plainly(denotes);
+                }
+                Denotes::Other
+            }
+        }
+    }
+
+    /// Walks the value (a literal, a range or a variable path) at `index`, and
+    /// advances past it (or past a single operator or keyword). Returns what
+    /// it denotes if it is a variable path.
+    fn value(
+        &mut self,
+        tokens: &[Token],
+        index: &mut usize,
+        file: &SourceFile,
+        scope: &mut Scope,
+    ) -> Option<Denotes> {
+        let token = &tokens[*index];
+        *index += 1;
+
+        match token.kind {
+            Kind::Ident if tokens.get(*index).is_some_and(|next| next.is_punct(":")) => {
+                // A named argument's key.
+                *index += 1;
+                None
+            }
+            Kind::Ident if !is_keyword(token.text) => {
+                Some(self.path(token, tokens, index, file, scope))
+            }
+            Kind::Punct if token.is_punct("(") => {
+                let end = closing(tokens, *index - 1, "(", ")");
+                let denotes = self.expression(&tokens[*index..end], file, scope);
+                self.use_plainly(denotes);
+                *index = (end + 1).min(tokens.len());
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Walks the variable path starting at `root`, records the reference to
+    /// its root variable, and resolves what it denotes.
+    fn path(
+        &mut self,
+        root: &Token,
+        tokens: &[Token],
+        index: &mut usize,
+        file: &SourceFile,
+        scope: &mut Scope,
+    ) -> Denotes {
+        let mut segments = Vec::new();
+        while let Some(token) = tokens.get(*index) {
+            match token.text {
+                "." if token.kind == Kind::Punct => match tokens.get(*index + 1) {
+                    Some(name) if name.kind == Kind::Ident => {
+                        segments.push((
+                            Segment::Name(name.text.to_string()),
+                            name.offset + name.text.len(),
+                        ));
+                        *index += 2;
+                    }
+                    _ => break,
+                },
+                "[" if token.kind == Kind::Punct => {
+                    let end = closing(tokens, *index, "[", "]");
+                    let inner = &tokens[*index + 1..end];
+                    let segment = match inner {
+                        [number] if number.kind == Kind::Number => {
+                            number.text.parse().map_or(Segment::Dynamic, Segment::Index)
+                        }
+                        [string] if string.kind == Kind::Str => {
+                            Segment::Name(string.text.to_string())
+                        }
+                        _ => {
+                            let denotes = self.expression(inner, file, scope);
+                            self.use_plainly(denotes);
+                            Segment::Dynamic
+                        }
+                    };
+                    *index = (end + 1).min(tokens.len());
+                    let last = &tokens[*index - 1];
+                    segments.push((segment, last.offset + last.text.len()));
+                }
+                _ => break,
+            }
+        }
+
+        scope.references.push((
+            root.text.to_string(),
+            file.path.to_path_buf(),
+            file.location(root.offset),
+        ));
+
+        let mut denotes = if let Some(denotes) = scope.aliases.get(root.text) {
+            *denotes
+        } else if scope.locals.contains(root.text) || !scope.globals {
+            Denotes::Other
+        } else if PALETTE_NAMES.contains(&root.text) {
+            Denotes::Palette
+        } else if root.text == "palettes" {
+            Denotes::PalettesBySlug
+        } else if root.text == "palette_list" {
+            Denotes::Palettes
+        } else if let Some(index) = self
+            .palette
+            .colors
+            .iter()
+            .rposition(|color| self.unrolled && color.name == root.text)
+        {
+            Denotes::Color(index)
+        } else {
+            Denotes::Other
+        };
+
+        for (segment, end) in &segments {
+            denotes = match (denotes, segment) {
+                (Denotes::Palettes | Denotes::PalettesBySlug, Segment::Name(name))
+                    if name == "size" =>
+                {
+                    Denotes::Other
+                }
+                (Denotes::Palettes | Denotes::PalettesBySlug, _) => Denotes::Palette,
+                (Denotes::Palette, Segment::Name(name)) if name == "colors" => Denotes::Colors,
+                (Denotes::Colors, Segment::Index(color_index)) => {
+                    let color_index = if *color_index < 0 {
+                        N as i64 + color_index
+                    } else {
+                        *color_index
+                    };
+                    match usize::try_from(color_index).ok().filter(|&index| index < N) {
+                        Some(color_index) => Denotes::Color(color_index),
+                        None => {
+                            let name = file.source.get(root.offset..*end).unwrap_or(root.text);
+                            let hint = format!(" (the palette has {} colors)", N);
+                            self.report(ReferenceKind::Color, name, file, root.offset, hint);
+                            Denotes::Other
+                        }
+                    }
+                }
+                (Denotes::Colors, Segment::Name(name)) if name == "first" && N > 0 => {
+                    Denotes::Color(0)
+                }
+                (Denotes::Colors, Segment::Name(name)) if name == "last" && N > 0 => {
+                    Denotes::Color(N - 1)
+                }
+                (Denotes::Colors, Segment::Dynamic) => {
+                    self.referenced = [true; N];
+                    Denotes::Other
+                }
+                (Denotes::Color(color_index), _) => Denotes::Color(color_index),
+                _ => Denotes::Other,
+            };
+        }
+
+        denotes
+    }
+
+    /// Marks the colors a value references when it is used as is (e.g.
+    /// output): a color, or all of a palette's colors, but not a whole palette.
+    fn use_plainly(&mut self, denotes: Denotes) {
+        match denotes {
+            Denotes::Color(index) => self.referenced[index] = true,
+            Denotes::Colors => self.referenced = [true; N],
+            _ => {}
+        }
+    }
+
+    /// Marks the color named by a string literal (e.g. a `ramp` argument) as
+    /// referenced, or reports it as unknown.
+    fn color_name(&mut self, token: &Token, file: &SourceFile) {
+        let mut found = false;
+        for (index, color) in self.palette.colors.iter().enumerate() {
+            if color.name == token.text {
+                self.referenced[index] = true;
+                found = true;
+            }
+        }
+
+        if !found {
+            let names: Vec<&str> = self
+                .palette
+                .colors
+                .iter()
+                .map(|color| color.name.as_str())
+                .collect();
+            let hint = did_you_mean(token.text, &names);
+            self.report(ReferenceKind::Color, token.text, file, token.offset, hint);
+        }
+    }
+
+    /// Reports the variables referenced in a scope but never defined in it.
+    fn check_references(&mut self, scope: Scope) {
+        let mut defined: Vec<&str> = scope
+            .locals
+            .iter()
+            .chain(scope.aliases.keys())
+            .map(String::as_str)
+            .collect();
+        if scope.globals {
+            defined.extend(&self.globals);
+        }
+
+        let unknown: Vec<UnknownReference> = scope
+            .references
+            .iter()
+            .filter(|(name, _, _)| {
+                !defined.contains(&name.as_str())
+                    && !LITERAL_NAMES.contains(&name.as_str())
+                    && !RUNTIME_NAMES.contains(&name.as_str())
+            })
+            .map(|(name, path, (line, column))| UnknownReference {
+                kind: ReferenceKind::Variable,
+                name: name.clone(),
+                path: path.clone(),
+                line: *line,
+                column: *column,
+                hint: undefined_hint(name, &defined, self.palette),
+            })
+            .collect();
+        for reference in unknown {
+            self.push_unknown(reference);
+        }
+    }
+
+    fn report(
+        &mut self,
+        kind: ReferenceKind,
+        name: &str,
+        file: &SourceFile,
+        offset: usize,
+        hint: String,
+    ) {
+        let (line, column) = file.location(offset);
+        self.push_unknown(UnknownReference {
+            kind,
+            name: name.to_string(),
+            path: file.path.to_path_buf(),
+            line,
+            column,
+            hint,
+        });
+    }
+
+    /// Records an unknown reference, unless the same name was already
+    /// reported in the same file.
+    fn push_unknown(&mut self, reference: UnknownReference) {
+        let reported = self.unknown.iter().any(|other| {
+            other.kind == reference.kind
+                && other.name == reference.name
+                && other.path == reference.path
+        });
+        if !reported {
+            self.unknown.push(reference);
+        }
+    }
+}
+
+/// Finds the names of the `{% increment %}` and `{% decrement %}` counters in a
+/// Liquid source (including in `{% liquid %}` tags).
+pub(crate) fn counters(source: &str) -> Vec<String> {
+    let mut counters = Vec::new();
+    for (offset, content, is_output) in tags(source) {
+        if is_output {
+            continue;
+        }
+        for line in content.lines() {
+            let tokens = tokenize(line, offset);
+            let words = match tokens.as_slice() {
+                [liquid, rest @ ..] if liquid.is_word("liquid") => rest,
+                tokens => tokens,
+            };
+            if let [tag, name, ..] = words {
+                if (tag.is_word("increment") || tag.is_word("decrement"))
+                    && !counters.iter().any(|counter| counter == name.text)
+                {
+                    counters.push(name.text.to_string());
+                }
+            }
+        }
+    }
+
+    counters
+}
+
+/// Whether a word is one of Liquid's operators or tag keywords, rather than
+/// a variable.
+fn is_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        "and"
+            | "or"
+            | "contains"
+            | "in"
+            | "with"
+            | "as"
+            | "for"
+            | "reversed"
+            | "empty"
+            | "blank"
+            | "nil"
+            | "null"
+            | "true"
+            | "false"
+    )
+}
+
+/// Returns the index of the token closing the one at `open`, or the end of
+/// the tokens if it is unclosed.
+fn closing(tokens: &[Token], open: usize, opening: &str, closing: &str) -> usize {
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        if token.kind != Kind::Punct {
+            continue;
+        }
+        if token.text == opening {
+            depth += 1;
+        } else if token.text == closing {
+            depth -= 1;
+            if depth == 0 {
+                return index;
+            }
+        }
+    }
+
+    tokens.len()
+}
+
+/// Finds the `{{ }}` and `{% %}` tags in a Liquid source, as the byte offset
+/// and contents (without whitespace control dashes) of each, and whether it
+/// is an output. Skips `{% raw %}` and `{% comment %}` blocks and `{% # %}`
+/// comments.
+fn tags(source: &str) -> Vec<(usize, &str, bool)> {
+    let mut tags = Vec::new();
+    let mut skipping: Option<&str> = None;
+    let mut position = 0;
+
+    while let Some(open) = source[position..]
+        .match_indices('{')
+        .map(|(index, _)| position + index)
+        .find(|&index| source[index + 1..].starts_with(['{', '%']))
+    {
+        let is_output = source[open + 1..].starts_with('{');
+        let delimiter = if is_output { "}}" } else { "%}" };
+        let start = open + 2;
+
+        let mut quote = None;
+        let mut end = None;
+        for (index, c) in source[start..].char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if source[start + index..].starts_with(delimiter) => {
+                    end = Some(start + index);
+                    break;
+                }
+                None => {}
+            }
+        }
+        let Some(end) = end else {
+            break;
+        };
+        position = end + 2;
+
+        let content = &source[start..end];
+        let trimmed = content.strip_prefix('-').unwrap_or(content);
+        let offset = start + content.len() - trimmed.len();
+        let trimmed = trimmed.strip_suffix('-').unwrap_or(trimmed);
+
+        let word = trimmed.split_whitespace().next().unwrap_or_default();
+        if let Some(closing) = skipping {
+            if !is_output && word == closing {
+                skipping = None;
+            }
+            continue;
+        }
+        match word {
+            "raw" if !is_output => skipping = Some("endraw"),
+            "comment" if !is_output => skipping = Some("endcomment"),
+            _ if !is_output && word.starts_with('#') => {}
+            _ => tags.push((offset, trimmed, is_output)),
+        }
+    }
+
+    tags
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Ident,
+    Str,
+    Number,
+    Punct,
+}
+
+/// A token of a tag's contents, with its byte offset in the source (of its
+/// opening quote, for a string).
+#[derive(Debug)]
+struct Token<'s> {
+    kind: Kind,
+    text: &'s str,
+    offset: usize,
+}
+
+impl Token<'_> {
+    fn is_punct(&self, punct: &str) -> bool {
+        self.kind == Kind::Punct && self.text == punct
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        self.kind == Kind::Ident && self.text == word
+    }
+}
+
+/// Splits a tag's contents, found at `offset` in the source, into tokens.
+fn tokenize(content: &str, offset: usize) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while let Some(c) = content[index..].chars().next() {
+        let rest = &content[index..];
+        let starts_number = |s: &str| s.starts_with(|d: char| d.is_ascii_digit());
+
+        let (kind, text, len) = if c.is_whitespace() {
+            index += c.len_utf8();
+            continue;
+        } else if c == '"' || c == '\'' {
+            let len = rest[1..].find(c).map_or(rest.len(), |end| end + 2);
+            (Kind::Str, &rest[1..len.max(2) - 1], len)
+        } else if starts_number(rest) || (c == '-' && starts_number(&rest[1..])) {
+            let digits = |s: &str| s.find(|d: char| !d.is_ascii_digit()).unwrap_or(s.len());
+            let mut len = 1 + digits(&rest[1..]);
+            if rest[len..].starts_with('.') && starts_number(&rest[len + 1..]) {
+                len += 1 + digits(&rest[len + 1..]);
+            }
+            (Kind::Number, &rest[..len], len)
+        } else if c.is_alphabetic() || c == '_' {
+            let mut len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(rest.len());
+            if rest[len..].starts_with('?') {
+                len += 1;
+            }
+            (Kind::Ident, &rest[..len], len)
+        } else {
+            let len = ["..", "==", "!=", "<>", "<=", ">="]
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .map_or(c.len_utf8(), |op| op.len());
+            (Kind::Punct, &rest[..len], len)
+        };
+
+        tokens.push(Token {
+            kind,
+            text,
+            offset: offset + index,
+        });
+        index += len;
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::BaseColor;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Palette<4> {
+        Palette::new(
+            "Test",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+                BaseColor::new("red", 47, 64, 38),
+                BaseColor::new("blue", 46, 4, -55),
+            ],
+        )
+    }
+
+    fn lint_source(
+        source: &str,
+        partials: &PartialFiles,
+        palette: &Palette<4>,
+        unroll: bool,
+    ) -> LintReport {
+        let file = SourceFile {
+            path: Path::new("test.liquid"),
+            source,
+            first_line: 1,
+        };
+        let options = RenderOptions {
+            unroll_colors_hex: unroll,
+            ..Default::default()
+        };
+
+        lint(&file, partials, palette, &options)
+    }
+
+    fn unreferenced(report: &LintReport) -> Vec<&str> {
+        report
+            .unreferenced
+            .iter()
+            .map(|color| color.name.as_str())
+            .collect()
+    }
+
+    #[rstest]
+    #[case::unrolled("{{ bg }} {{ fg | upcase }}{% if red %}{% endif %}", true, vec!["blue"])]
+    #[case::indexed("{{ palette.colors[0].srgb_hex }}{{ palette.colors.last.srgb_hex }}", false, vec!["fg", "red"])]
+    #[case::negative_index("{{ palette.colors[-2].srgb_hex }}", false, vec!["bg", "fg", "blue"])]
+    #[case::iterated("{% for color in palette.colors %}{{ color.srgb_hex }}{% endfor %}", false, vec![])]
+    #[case::dynamic_index("{% for i in (0..1) %}{{ palette.colors[i].srgb_hex }}{% endfor %}", false, vec![])]
+    #[case::ramp(r#"{% assign shades = palette | ramp: "bg", "red", 3 %}{{ shades.first.srgb_hex }}"#, false, vec!["fg", "blue"])]
+    #[case::where_name(r#"{{ palette.colors | where: "name", "blue" | map: "srgb_hex" }}"#, false, vec!["bg", "fg", "red"])]
+    #[case::alias("{% assign p = palette_list[1] %}{% assign cs = p.colors %}{{ cs[1].srgb_hex }}", false, vec!["bg", "red", "blue"])]
+    #[case::by_slug(r#"{{ palettes["my-palette"].colors[0].srgb_hex }}{{ palettes.dark.colors[1].srgb_hex }}"#, false, vec!["red", "blue"])]
+    #[case::palette_only("{{ palette.name }}: {{ light.slug }}", false, vec!["bg", "fg", "red", "blue"])]
+    #[case::raw("{% raw %}{{ bg }}{% endraw %}{% comment %}{{ fg }}{% endcomment %}{{ red }}", true, vec!["bg", "fg", "blue"])]
+    #[case::liquid_tag("{%- liquid\n  assign c = palette.colors[3]\n  echo c.srgb_hex\n-%}", false, vec!["bg", "fg", "red"])]
+    fn test_lint_unreferenced(
+        palette: Palette<4>,
+        #[case] source: &str,
+        #[case] unroll: bool,
+        #[case] expected: Vec<&str>,
+    ) {
+        let report = lint_source(source, &PartialFiles::default(), &palette, unroll);
+        assert_eq!(report.unknown, vec![]);
+        assert_eq!(unreferenced(&report), expected);
+    }
+
+    #[rstest]
+    fn test_lint_unknown(palette: Palette<4>) {
+        let source = "{% assign x = 1 %}{{ x }}\n  {{ bg }} {{ palete.name }}{{ bgg }}\n{{ palette.colors[4].srgb_hex }}{{ palette | ramp: \"bg\", \"pink\", 2 }}{% render \"missing\" %}";
+        let report = lint_source(source, &PartialFiles::default(), &palette, false);
+
+        let unknown: Vec<String> = report.unknown.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            unknown,
+            vec![
+                "test.liquid:3:4: unknown color \"palette.colors[4]\" (the palette has 4 colors)",
+                "test.liquid:3:58: unknown color \"pink\"",
+                "test.liquid:3:80: unknown partial \"missing\"",
+                "test.liquid:2:6: unknown variable \"bg\" (palette color names are only variables when unrolling colors as hex)",
+                "test.liquid:2:15: unknown variable \"palete\" (did you mean \"palette\" or \"palettes\"?)",
+                "test.liquid:2:32: unknown variable \"bgg\"",
+            ]
+        );
+        assert_eq!(unreferenced(&report), vec!["fg", "red", "blue"]);
+    }
+
+    #[rstest]
+    fn test_lint_partials(palette: Palette<4>) {
+        let mut partials = PartialFiles::default();
+        partials.files.insert(
+            "header.liquid".to_string(),
+            (
+                PathBuf::from("header.liquid"),
+                "{{ fg }}{% include \"header.liquid\" %}".to_string(),
+            ),
+        );
+        partials.files.insert(
+            "swatch.liquid".to_string(),
+            (
+                PathBuf::from("swatch.liquid"),
+                "{{ p.colors[2].srgb_hex }}{{ title }}{{ bg }}".to_string(),
+            ),
+        );
+        let source = "{% include \"header.liquid\" %}{% render \"swatch\", p: palette %}";
+
+        let report = lint_source(source, &partials, &palette, true);
+        let unknown: Vec<String> = report.unknown.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            unknown,
+            vec![
+                "swatch.liquid:1:30: unknown variable \"title\"",
+                "swatch.liquid:1:41: unknown variable \"bg\" (palette color names are only variables when unrolling colors as hex)",
+            ]
+        );
+        assert_eq!(unreferenced(&report), vec!["bg", "blue"]);
+    }
+
+    #[rstest]
+    fn test_tags() {
+        let source = "a {{- bg | append: \"}}\" }} {% if x -%}{%raw%}{{ y }}{%endraw%}{% # z %}";
+        let tags: Vec<(usize, &str, bool)> = tags(source);
+        assert_eq!(
+            tags,
+            vec![(5, " bg | append: \"}}\" ", true), (29, " if x ", false)]
+        );
+    }
+
+    #[rstest]
+    fn test_tokenize() {
+        let tokens = tokenize("a.b[-1] | f: 'x y', 1.5 (1..n) c?", 10);
+        let texts: Vec<(Kind, &str, usize)> = tokens
+            .iter()
+            .map(|token| (token.kind, token.text, token.offset))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (Kind::Ident, "a", 10),
+                (Kind::Punct, ".", 11),
+                (Kind::Ident, "b", 12),
+                (Kind::Punct, "[", 13),
+                (Kind::Number, "-1", 14),
+                (Kind::Punct, "]", 16),
+                (Kind::Punct, "|", 18),
+                (Kind::Ident, "f", 20),
+                (Kind::Punct, ":", 21),
+                (Kind::Str, "x y", 23),
+                (Kind::Punct, ",", 28),
+                (Kind::Number, "1.5", 30),
+                (Kind::Punct, "(", 34),
+                (Kind::Number, "1", 35),
+                (Kind::Punct, "..", 36),
+                (Kind::Ident, "n", 38),
+                (Kind::Punct, ")", 39),
+                (Kind::Ident, "c?", 41),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::output("a\n  {{ bg_3 }}", Some((2, 6)))]
+    #[case::condition("{% if x %}{% endif %}{% if bg_3 %}", Some((1, 28)))]
+    #[case::attribute("{{ palette.bg_3 }} {{ bg_3 | upcase }}", Some((1, 23)))]
+    #[case::string("{{ \"bg_3\" }}{% assign x = 'bg_3' %}\n{{ bg_3 }}", Some((2, 4)))]
+    #[case::text("bg_3 {{ bg_30 }}", None)]
+    fn test_locate(#[case] source: &str, #[case] location: Option<(usize, usize)>) {
+        let file = SourceFile {
+            path: Path::new("test.liquid"),
+            source,
+            first_line: 1,
+        };
+        assert_eq!(file.locate("bg_3"), location);
+    }
+
+    #[rstest]
+    fn test_counters() {
+        let source =
+            "{% increment a %}{{ b }}{% decrement c %}{% liquid\nincrement a\ndecrement d %}";
+        assert_eq!(counters(source), vec!["a", "c", "d"]);
+    }
+}
@@ -1,21 +1,41 @@
-use anyhow::{Context, Result};
-use glob::glob;
-use liquid::model::{to_value, KString};
+use anyhow::{anyhow, bail, Context, Result};
+use liquid::model::{to_value, KString, Value};
 use liquid::partials::{EagerCompiler, InMemorySource, PartialSource};
 use liquid::{Object, Parser, ParserBuilder, Template};
 use std::fs::read_to_string;
+use std::iter;
 use std::path::{Path, PathBuf};
 
-use crate::palette::{DerivedPalette, Palette};
-use crate::template::{PaletteRenderer, RenderOptions};
+use crate::palette::{slug, DerivedPalette, Palette};
+use crate::serialize::{from_str, Format};
+use crate::template::filters::Ramp;
+use crate::template::lint::{counters, lint, SourceFile};
+use crate::template::strict::{undefined_hint, StrictGlobals};
+use crate::template::{
+    FrontMatter, LintReport, PaletteRenderer, PartialFiles, PartialOptions, PartialsDir,
+    RenderOptions,
+};
+use crate::variant::Polarity;
 
 /// Represents a parsed Liquid template.
 pub struct LiquidTemplate {
-    /// Path to the Liquid template file.
+    /// Path to the Liquid template file (or the name of a template string).
     path: PathBuf,
 
     /// A parsed Liquid template object.
     template: Template,
+
+    /// The template's metadata, from its front-matter.
+    front_matter: FrontMatter,
+
+    /// The template's source (without its front-matter), for diagnostics.
+    source: String,
+
+    /// The line number, in the template file, that `source` starts on.
+    first_line: usize,
+
+    /// The template partials the template was parsed with.
+    partials: PartialFiles,
 }
 
 type Partials = EagerCompiler<InMemorySource>;
@@ -24,36 +44,125 @@ impl LiquidTemplate {
     /// Instantiates a LiquidTemplate by parsing the given file.
     ///
     /// The resulting template object will be ready for rendering given context.
+    /// The file's YAML front-matter, if any, is stripped from the template and
+    /// exposed by [`LiquidTemplate::front_matter`].
     ///
     /// * `path` - The path to the file to parse as a Liquid template.
     /// * `partials_dirs` - Paths to directories, if any, where template partials
-    ///   can be searched for `{% render %}` or `{% include %}` directive tags.
+    ///   can be searched for `{% render %}` or `{% include %}` directive tags
+    ///   (see [`PartialFiles`], loaded with the default options).
     pub fn parse_file(path: &Path, partials_dirs: Vec<PathBuf>) -> Result<Self> {
-        let parser = LiquidTemplate::build_parser(partials_dirs)?;
+        let partials_dirs: Vec<PartialsDir> =
+            partials_dirs.into_iter().map(PartialsDir::from).collect();
+        let partials = PartialFiles::load(&partials_dirs, &PartialOptions::default())?;
+        LiquidTemplate::parse_file_with(path, &partials)
+    }
+
+    /// Instantiates a LiquidTemplate by parsing the given file, as
+    /// [`LiquidTemplate::parse_file`] does, with already loaded partials.
+    ///
+    /// * `path` - The path to the file to parse as a Liquid template.
+    /// * `partials` - The template partials for `{% render %}` or `{% include %}`
+    ///   directive tags.
+    pub fn parse_file_with(path: &Path, partials: &PartialFiles) -> Result<Self> {
+        let source = read_to_string(path)
+            .with_context(|| format!("Could not read Liquid template file: \"{:?}\"", path))?;
+
+        LiquidTemplate::parse_str_with(&source, path, partials)
+    }
+
+    /// Instantiates a LiquidTemplate by parsing the given source string, as
+    /// [`LiquidTemplate::parse_file`] does a file's contents.
+    ///
+    /// * `source` - The Liquid template source.
+    /// * `name` - The name of the template (e.g. "<stdin>"), for error messages.
+    /// * `partials_dirs` - Paths to directories, if any, where template partials
+    ///   can be searched for `{% render %}` or `{% include %}` directive tags.
+    pub fn parse_str<P>(source: &str, name: P, partials_dirs: Vec<PathBuf>) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let partials_dirs: Vec<PartialsDir> =
+            partials_dirs.into_iter().map(PartialsDir::from).collect();
+        let partials = PartialFiles::load(&partials_dirs, &PartialOptions::default())?;
+        LiquidTemplate::parse_str_with(source, name, &partials)
+    }
+
+    /// Instantiates a LiquidTemplate by parsing the given source string, as
+    /// [`LiquidTemplate::parse_str`] does, with already loaded partials.
+    ///
+    /// * `source` - The Liquid template source.
+    /// * `name` - The name of the template (e.g. "<stdin>"), for error messages.
+    /// * `partials` - The template partials for `{% render %}` or `{% include %}`
+    ///   directive tags.
+    pub fn parse_str_with<P>(source: &str, name: P, partials: &PartialFiles) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = name.as_ref();
+        let parser = LiquidTemplate::build_parser(partials)?;
+
+        let (front_matter, body) = FrontMatter::split(source)
+            .with_context(|| format!("Could not parse Liquid template: \"{:?}\"", path))?;
+        let first_line = source[..source.len() - body.len()].matches('\n').count() + 1;
 
         let template = parser
-            .parse_file(path)
-            .with_context(|| format!("Could not parse Liquid template file: \"{:?}\"", path))?;
+            .parse(body)
+            .with_context(|| format!("Could not parse Liquid template: \"{:?}\"", path))?;
 
         Ok(Self {
             path: path.to_path_buf(),
             template,
+            front_matter,
+            source: body.to_string(),
+            first_line,
+            partials: partials.clone(),
         })
     }
 
+    /// Returns this template's metadata, from its front-matter (or the default,
+    /// if it has none).
+    pub fn front_matter(&self) -> &FrontMatter {
+        &self.front_matter
+    }
+
+    /// Returns the template partials this template was parsed with, and their
+    /// conflicts.
+    pub fn partials(&self) -> &PartialFiles {
+        &self.partials
+    }
+
+    /// Lints this template (and the partials it includes or renders) against
+    /// a palette it would be rendered with: reports the palette colors it never
+    /// references, and the names it references that do not exist (see
+    /// [`LintReport`]).
+    pub fn lint<const N: usize>(
+        &self,
+        palette: &Palette<N>,
+        options: &RenderOptions,
+    ) -> LintReport {
+        let file = SourceFile {
+            path: &self.path,
+            source: &self.source,
+            first_line: self.first_line,
+        };
+
+        lint(&file, &self.partials, palette, options)
+    }
+
     /// Builds a Liquid Parser and, optionally, preload it with template partials.
-    fn build_parser(partials_dirs: Vec<PathBuf>) -> Result<Parser> {
+    fn build_parser(partial_files: &PartialFiles) -> Result<Parser> {
         let partials = {
             let mut _partials = Partials::empty();
-            for dirpath in partials_dirs {
-                LiquidTemplate::parse_partials(dirpath.as_path(), &mut _partials)?;
+            for (name, (_, contents)) in &partial_files.files {
+                _partials.add(name.as_str(), contents.as_str());
             }
             _partials
         };
 
         let has_partials = !partials.names().is_empty();
         let parser = {
-            let mut builder = ParserBuilder::with_stdlib();
+            let mut builder = ParserBuilder::with_stdlib().filter(Ramp);
             builder = if has_partials {
                 builder.partials(partials)
             } else {
@@ -65,43 +174,188 @@ impl LiquidTemplate {
 
         Ok(parser)
     }
+}
 
-    /// Parses all `.liquid` files in the given directory, and insert them into the given partials.
-    fn parse_partials(dirpath: &Path, partials: &mut Partials) -> Result<()> {
-        let pattern = format!("{}/*.liquid", dirpath.to_str().unwrap());
-        let matching_paths = glob(&pattern)?;
+impl LiquidTemplate {
+    /// Returns the names of the `{% increment %}` and `{% decrement %}` counters
+    /// in this template and its partials.
+    fn counters(&self) -> Vec<String> {
+        let mut names = counters(&self.source);
+        for (_, source) in self.partials.files.values() {
+            names.extend(counters(source));
+        }
 
-        for path in matching_paths.filter_map(core::result::Result::ok) {
-            let basename = String::from(path.file_name().unwrap().to_str().unwrap());
-            let filepath = String::from(path.to_str().unwrap());
-            let contents = read_to_string(path)
-                .with_context(|| format!("Could not read partial file: {}", filepath))?;
+        names
+    }
 
-            // TODO: Handle the case when basename conflicts (with same-named file from a different
-            // directory).
-            partials.add(basename, contents);
-        }
+    /// Finds the first reference to a variable in this template's source, or
+    /// else in its partials' sources, as a `path:line:column` location (or
+    /// just this template's path, if not found).
+    fn locate(&self, name: &str) -> String {
+        let template = SourceFile {
+            path: &self.path,
+            source: &self.source,
+            first_line: self.first_line,
+        };
+        let partials = self
+            .partials
+            .files
+            .values()
+            .map(|(path, source)| SourceFile {
+                path,
+                source,
+                first_line: 1,
+            });
+
+        iter::once(template)
+            .chain(partials)
+            .find_map(|file| {
+                let (line, column) = file.locate(name)?;
+                Some(format!("{}:{}:{}", file.path.display(), line, column))
+            })
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
 
-        Ok(())
+    /// Describes the undefined variables referenced by this template, with
+    /// their locations in its source and the closest variable names defined.
+    fn undefined_variables_error<const N: usize>(
+        &self,
+        undefined: &[String],
+        globals: &Object,
+        palette: &Palette<N>,
+    ) -> anyhow::Error {
+        let mut defined: Vec<&str> = globals.keys().map(|name| name.as_str()).collect();
+        defined.sort_unstable();
+
+        let diagnostics: Vec<String> = undefined
+            .iter()
+            .map(|name| {
+                let location = self.locate(name);
+                let hint = undefined_hint(name, &defined, palette);
+                format!("{}: undefined variable \"{}\"{}", location, name, hint)
+            })
+            .collect();
+
+        anyhow!(
+            "Could not render Liquid template \"{:?}\" in strict variables mode:\n{}",
+            self.path,
+            diagnostics.join("\n")
+        )
     }
 }
 
+/// The names of the palette variables injected into every template.
+const RESERVED_NAMES: [&str; 5] = ["palette", "palettes", "palette_list", "light", "dark"];
+
+/// Converts a palette into a Liquid value: its derived palette, with its slug.
+fn palette_value<const N: usize>(palette: &Palette<N>) -> Result<Value> {
+    let derived_palette = DerivedPalette::from(palette);
+    let mut value = to_value(&derived_palette).with_context(|| {
+        format!(
+            "Could not serialize derived palette:\n{:?}",
+            derived_palette
+        )
+    })?;
+    if let Value::Object(obj) = &mut value {
+        obj.insert("slug".into(), Value::scalar(slug(&palette.name)));
+    }
+
+    Ok(value)
+}
+
+/// Returns the key ("light" or "dark") to also inject a palette with, if it is
+/// a 16-color (Base16) palette.
+fn polarity_key<const N: usize>(palette: &Palette<N>) -> Option<&'static str> {
+    match palette.base16_polarity()? {
+        Polarity::Light => Some("light"),
+        Polarity::Dark => Some("dark"),
+    }
+}
+
+/// Loads extra template variables (see [`RenderOptions::variables`]) from a
+/// file of key-value pairs, choosing its deserializer by the file's extension
+/// (YAML, unless otherwise recognized).
+pub fn load_variables(path: &Path) -> Result<Object> {
+    let format = Format::from_path(path).unwrap_or(Format::Yaml);
+    let s = read_to_string(path)
+        .with_context(|| format!("Could not read variables file {}", path.display()))?;
+
+    from_str(&s, format).with_context(|| {
+        format!(
+            "Could not deserialize {} to variables: {}",
+            format,
+            path.display()
+        )
+    })
+}
+
 impl<const N: usize> PaletteRenderer<N> for LiquidTemplate {
-    /// Renders this Liquid template with an injection of the given palette.
-    ///
-    /// The given `palette` will be converted into a `liquid::Object` value
-    /// and injected as a variable in the rendered template with the key `"palette"`.
+    /// Renders this Liquid template with an injection of the given palette (see
+    /// [`PaletteRenderer::render_palettes`]).
     fn render(&self, palette: &Palette<N>, options: RenderOptions) -> Result<String> {
-        let derived_palette = DerivedPalette::from(palette);
+        self.render_palettes(&[palette], options)
+    }
+
+    /// Renders this Liquid template with an injection of the given palettes.
+    ///
+    /// Each palette is converted into a `liquid::Object` value (with an added
+    /// `slug`, see [`slug`]). The first palette is injected with the key
+    /// `"palette"`, and all of them as an object keyed by their slugs with the
+    /// key `"palettes"` (their slugs must be unique), and, in order, as a list
+    /// with the key `"palette_list"`. For 16-color palettes, the first light
+    /// and the first dark one, if any, are also injected with the keys
+    /// `"light"` and `"dark"`.
+    fn render_palettes(&self, palettes: &[&Palette<N>], options: RenderOptions) -> Result<String> {
+        let Some(palette) = palettes.first() else {
+            bail!(
+                "Could not render Liquid template \"{:?}\": no palette given",
+                self.path
+            );
+        };
+
+        if let Some(palette_size) = self.front_matter.palette_size {
+            if palette_size != N {
+                bail!(
+                    "Liquid template \"{:?}\" requires a palette of {} colors, found {}",
+                    self.path,
+                    palette_size,
+                    N
+                );
+            }
+        }
+
+        let derived_palette = DerivedPalette::from(*palette);
 
-        let palette_obj_value = to_value(&derived_palette).with_context(|| {
-            format!(
-                "Could not serialize derived palette:\n{:?}",
-                derived_palette
-            )
-        })?;
         let mut obj = Object::new();
-        obj.insert("palette".into(), palette_obj_value);
+        obj.insert("palette".into(), palette_value(palette)?);
+
+        let mut palettes_by_slug = Object::new();
+        let mut palette_list = Vec::new();
+        for (index, palette) in palettes.iter().enumerate() {
+            // The first palette was already injected as "palette".
+            let value = if index == 0 {
+                obj["palette"].clone()
+            } else {
+                palette_value(palette)?
+            };
+            if let Some(key) = polarity_key(palette) {
+                if !obj.contains_key(key) {
+                    obj.insert(key.into(), value.clone());
+                }
+            }
+            let slug = slug(&palette.name);
+            if palettes_by_slug.contains_key(slug.as_str()) {
+                bail!(
+                    "Could not render Liquid template \"{:?}\": several palettes have the slug \"{}\"",
+                    self.path,
+                    slug
+                );
+            }
+            palettes_by_slug.insert(slug.into(), value.clone());
+            palette_list.push(value);
+        }
+        obj.insert("palettes".into(), Value::Object(palettes_by_slug));
+        obj.insert("palette_list".into(), Value::Array(palette_list));
 
         // Insert each color's sRGB hex string as values keyed to the color's names.
         if options.unroll_colors_hex {
@@ -119,14 +373,38 @@ impl<const N: usize> PaletteRenderer<N> for LiquidTemplate {
             }
         }
 
-        let rendered = self.template.render(&obj).with_context(|| {
-            format!(
-                "Could not render Liquid template \"{:?}\" with derived palette:\n{:?}",
-                self.path, derived_palette
-            )
-        })?;
+        // Insert the extra variables, which must not shadow any of the above.
+        for (name, value) in options.variables {
+            if obj.contains_key(name.as_str()) {
+                bail!(
+                    "Could not render Liquid template \"{:?}\": variable \"{}\" collides with {}",
+                    self.path,
+                    name,
+                    if RESERVED_NAMES.contains(&name.as_str()) {
+                        "a palette"
+                    } else {
+                        "a palette color name"
+                    }
+                );
+            }
+            obj.insert(name, value);
+        }
 
-        Ok(rendered)
+        if !options.strict_variables {
+            return self
+                .template
+                .render(&obj)
+                .with_context(|| format!("Could not render Liquid template \"{:?}\"", self.path));
+        }
+
+        let globals = StrictGlobals::new(&obj, self.counters());
+        let rendered = self.template.render(&globals);
+        let undefined = globals.undefined();
+        if !undefined.is_empty() {
+            return Err(self.undefined_variables_error(&undefined, &obj, palette));
+        }
+
+        rendered.with_context(|| format!("Could not render Liquid template \"{:?}\"", self.path))
     }
 }
 
@@ -134,10 +412,10 @@ impl<const N: usize> PaletteRenderer<N> for LiquidTemplate {
 mod tests {
     use super::*;
     use crate::palette::{Base16Palette, BaseColor};
+    use crate::template::PartialPrecedence;
+    use crate::test_utils::*;
 
     use rstest::*;
-    use std::fs::write;
-    use tempdir::TempDir;
 
     #[fixture]
     fn palette() -> Base16Palette {
@@ -165,10 +443,6 @@ mod tests {
         )
     }
 
-    struct TempDirFixture {
-        tmpdir: TempDir,
-    }
-
     const LIQUID_TEMPLATE_FILENAME: &str = "test.liquid";
 
     impl TempDirFixture {
@@ -214,30 +488,6 @@ mod tests {
 
             LiquidTemplate::parse_file(tempfile_path.as_path(), dirpaths)
         }
-
-        /// Writes the given UTF-8 contents string into a file in this TempDir fixture.
-        ///
-        /// Returns a full filepath to the newly created file.
-        fn write_to_file(&self, filename: &str, contents: &str) -> Result<PathBuf> {
-            let filepath = self.tmpdir.path().join(filename);
-            write(filepath.clone(), contents)?;
-
-            Ok(filepath)
-        }
-    }
-
-    #[fixture]
-    fn tmpdir() -> TempDirFixture {
-        TempDirFixture {
-            tmpdir: TempDir::new("tests").unwrap(),
-        }
-    }
-
-    #[fixture]
-    fn tmpdir_2() -> TempDirFixture {
-        TempDirFixture {
-            tmpdir: TempDir::new("tests_2").unwrap(),
-        }
     }
 
     #[rstest]
@@ -268,7 +518,13 @@ mod tests {
 
         let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
 
-        let rendered = liquid_template.render(&palette, RenderOptions { unroll_colors_hex: true })?;
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
 
         assert_eq!(liquid_template_rendered, rendered);
 
@@ -288,7 +544,13 @@ mod tests {
 
         let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
 
-        let rendered = liquid_template.render(&palette, RenderOptions { unroll_colors_hex: true })?;
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
 
         assert_eq!(liquid_template_rendered, rendered);
 
@@ -307,7 +569,13 @@ mod tests {
 
         let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
 
-        let result = liquid_template.render(&palette, RenderOptions { unroll_colors_hex: false });
+        let result = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: false,
+                ..Default::default()
+            },
+        );
         result.expect_err("Should not have been able to render template with unrolled color names");
 
         Ok(())
@@ -332,7 +600,13 @@ mod tests {
         let liquid_template =
             tmpdir.create_liquid_template_with_partials(liquid_template_content)?;
 
-        let rendered = liquid_template.render(&palette, RenderOptions { unroll_colors_hex: true })?;
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
         assert_eq!(liquid_template_rendered, rendered);
 
         Ok(())
@@ -371,9 +645,429 @@ mod tests {
             vec![tmpdir_2.tmpdir.path().to_path_buf()],
         )?;
 
-        let rendered = liquid_template.render(&palette, RenderOptions { unroll_colors_hex: true })?;
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(liquid_template_rendered, rendered);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_with_conflicting_partials(
+        tmpdir: TempDirFixture,
+        tmpdir_2: TempDirFixture,
+        palette: Base16Palette,
+    ) -> Result<()> {
+        let namespace = tmpdir_2
+            .tmpdir
+            .path()
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let liquid_template_content = format!(
+            r#"{{% include "header.liquid" %}} {{% include "{}/header.liquid" %}}"#,
+            namespace
+        );
+
+        tmpdir.write_to_file("header.liquid", "first")?;
+        tmpdir_2.write_to_file("header.liquid", "second")?;
+        let template_path =
+            tmpdir.write_to_file(LIQUID_TEMPLATE_FILENAME, &liquid_template_content)?;
+        let dirpaths = vec![
+            PartialsDir::from(tmpdir.tmpdir.path().to_path_buf()),
+            PartialsDir::from(tmpdir_2.tmpdir.path().to_path_buf()),
+        ];
+
+        let options = PartialOptions {
+            precedence: PartialPrecedence::First,
+            ..Default::default()
+        };
+        let partials = PartialFiles::load(&dirpaths, &options)?;
+        let liquid_template = LiquidTemplate::parse_file_with(&template_path, &partials)?;
+        assert_eq!(liquid_template.partials().conflicts().len(), 1);
+
+        let rendered = liquid_template.render(&palette, RenderOptions::default())?;
+        assert_eq!(rendered, "first second");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_ramp_filter(tmpdir: TempDirFixture, palette: Base16Palette) -> Result<()> {
+        let liquid_template_content = r#"
+            {%- assign shades = palette | ramp: "bg_0", "bg_2", 3 %}
+            {%- for color in shades %}
+                {{ color.base.name }}: #{{ color.srgb_hex }}
+            {%- endfor %}
+            {%- assign shades = palette.colors | ramp: "bg_0", "blue", 2, "lch" %}
+                {{ shades.last.srgb_hex }}
+        "#;
+        let liquid_template_rendered = r#"
+                bg_0_bg_2_0: #fef3da
+                bg_0_bg_2_1: #eadfc7
+                bg_0_bg_2_2: #d6cbb4
+                0073d2
+        "#;
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: false,
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(liquid_template_rendered, rendered);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_ramp_filter_unknown_color(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+    ) -> Result<()> {
+        let liquid_template_content = r#"{{ palette | ramp: "bg_0", "pink", 3 }}"#;
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+
+        let result = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: false,
+                ..Default::default()
+            },
+        );
+        result.expect_err("Should not have been able to ramp to an unknown color");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_front_matter(tmpdir: TempDirFixture, palette: Base16Palette) -> Result<()> {
+        let liquid_template_content =
+            "---\nvariant: light\npalette_size: 16\n---\nbg_0: #{{ bg_0 }}\n";
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+        assert_eq!(
+            liquid_template.front_matter().variant,
+            Some(Polarity::Light)
+        );
+
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!("bg_0: #fef3da\n", rendered);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::document("---\nprimary:\n  background: \"#{{ bg_0 }}\"\n")]
+    #[case::documents("---\nprimary:\n  background: \"#{{ bg_0 }}\"\n---\nsecondary: {}\n")]
+    fn test_render_yaml_document_marker(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+        #[case] liquid_template_content: &str,
+    ) -> Result<()> {
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+        assert_eq!(liquid_template.front_matter(), &FrontMatter::default());
+
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            liquid_template_content.replace("{{ bg_0 }}", "fef3da"),
+            rendered
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_front_matter_palette_size(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+    ) -> Result<()> {
+        let liquid_template_content = "---\npalette_size: 8\n---\nbg_0: #{{ bg_0 }}\n";
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+
+        let result = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        );
+        result.expect_err("Should not have been able to render a palette of the wrong size");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parse_str(tmpdir: TempDirFixture, palette: Base16Palette) -> Result<()> {
+        tmpdir.write_to_file("common.liquid", "{{ palette.name }}:")?;
+
+        let liquid_template = LiquidTemplate::parse_str(
+            r#"{% include "common.liquid" %} #{{ bg_0 }}"#,
+            "inline",
+            vec![tmpdir.tmpdir.path().to_path_buf()],
+        )?;
+
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!("Selenized light: #fef3da", rendered);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parse_str_invalid() {
+        let result = LiquidTemplate::parse_str("{{ bg_0", "inline", Vec::new());
+        let err = result
+            .err()
+            .expect("Should not have been able to parse an unclosed tag");
+        assert!(err.to_string().contains("\"inline\""));
+    }
+
+    #[rstest]
+    fn test_render_variables(tmpdir: TempDirFixture, palette: Base16Palette) -> Result<()> {
+        let variables_path =
+            tmpdir.write_to_file("vars.yaml", "font:\n  family: Iosevka\n  size: 12\n")?;
+        let mut variables = load_variables(&variables_path)?;
+        variables.insert("opacity".into(), to_value(&0.9)?);
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(
+            "{{ font.family }} {{ font.size | plus: 1 }} {{ opacity }} #{{ bg_0 }}",
+        )?;
+
+        let rendered = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                variables,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!("Iosevka 13 0.9 #fef3da", rendered);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::palette("palette")]
+    #[case::color_name("bg_0")]
+    fn test_render_variables_collision(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+        #[case] name: &str,
+    ) -> Result<()> {
+        let mut variables = Object::new();
+        variables.insert(KString::from_ref(name), to_value(&"collides")?);
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials("#{{ bg_0 }}")?;
+
+        let result = liquid_template.render(
+            &palette,
+            RenderOptions {
+                unroll_colors_hex: true,
+                variables,
+                ..Default::default()
+            },
+        );
+        result.expect_err("Should not have been able to shadow a palette variable");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_palettes(tmpdir: TempDirFixture, palette: Base16Palette) -> Result<()> {
+        let dark_palette = palette.opposite_variant("Selenized dark");
+        let liquid_template_content = r#"
+            {%- for palette in palette_list %}
+                {{ palette.slug }}: #{{ palette.colors.first.srgb_hex }}
+            {%- endfor %}
+                {{ palette.name }}, {{ light.name }}, {{ dark.name }}
+            {%- assign selected = palettes["selenized-dark"] %}
+                {{ selected.name }}: #{{ bg_0 }}, {{ palettes.selenized-light.name }}
+        "#;
+        let liquid_template_rendered = r#"
+                selenized-dark: #053d48
+                selenized-light: #fef3da
+                Selenized dark, Selenized light, Selenized dark
+                Selenized dark: #053d48, Selenized light
+        "#;
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+
+        let rendered = liquid_template.render_palettes(
+            &[&dark_palette, &palette],
+            RenderOptions {
+                unroll_colors_hex: true,
+                ..Default::default()
+            },
+        )?;
         assert_eq!(liquid_template_rendered, rendered);
 
         Ok(())
     }
+
+    #[rstest]
+    fn test_render_palettes_none(tmpdir: TempDirFixture) -> Result<()> {
+        let liquid_template = tmpdir.create_liquid_template_no_partials("{{ palette.name }}")?;
+
+        let result =
+            PaletteRenderer::<16>::render_palettes(&liquid_template, &[], RenderOptions::default());
+        result.expect_err("Should not have been able to render without a palette");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_palettes_duplicate_slugs(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+    ) -> Result<()> {
+        let liquid_template = tmpdir.create_liquid_template_no_partials("{{ palette.name }}")?;
+        let renamed = Base16Palette {
+            name: "Selenized-Light".to_string(),
+            ..palette.clone()
+        };
+
+        let err = liquid_template
+            .render_palettes(&[&palette, &renamed], RenderOptions::default())
+            .expect_err("Should not have been able to render palettes with the same slug");
+        assert!(err
+            .to_string()
+            .ends_with("several palettes have the slug \"selenized-light\""));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_strict_variables(tmpdir: TempDirFixture, palette: Base16Palette) -> Result<()> {
+        let liquid_template_content = "---\npalette_size: 16\n---\nbg_0: #{{ bg_0 }}\n{% if bg_3 %}\n  #{{ bg_3 }}\n{% endif %}{{ palete.name }}";
+
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+
+        let err = liquid_template
+            .render(
+                &palette,
+                RenderOptions {
+                    unroll_colors_hex: true,
+                    strict_variables: true,
+                    ..Default::default()
+                },
+            )
+            .expect_err("Should not have been able to render undefined variables in strict mode");
+        let path = tmpdir.tmpdir.path().join(LIQUID_TEMPLATE_FILENAME);
+        assert_eq!(
+            err.to_string().split_once('\n').unwrap().1,
+            format!(
+                "{0}:5:7: undefined variable \"bg_3\" (did you mean \"bg_0\", \"bg_1\" or \"bg_2\"?)\n\
+                 {0}:7:15: undefined variable \"palete\" (did you mean \"palette\" or \"palettes\"?)",
+                path.display()
+            )
+        );
+        assert!(!format!("{:?}", err).contains("DerivedPalette"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_strict_variables_not_unrolled(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+    ) -> Result<()> {
+        let liquid_template = tmpdir.create_liquid_template_no_partials("{{ bg_0 }}")?;
+
+        let err = liquid_template
+            .render(
+                &palette,
+                RenderOptions {
+                    strict_variables: true,
+                    ..Default::default()
+                },
+            )
+            .expect_err("Should not have been able to render a color name without unrolling");
+        assert!(err
+            .to_string()
+            .contains(":1:4: undefined variable \"bg_0\" (palette color names"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_strict_variables_in_partial(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+    ) -> Result<()> {
+        let partial_path = tmpdir.write_to_file("swatch.liquid", "#{{ bg_0 }}\n#{{ bg_9 }}")?;
+        let liquid_template = tmpdir.create_liquid_template_with_partials(
+            "{{ \"bg_9\" }}: {% include 'swatch.liquid' %}",
+        )?;
+
+        let options = RenderOptions {
+            unroll_colors_hex: true,
+            strict_variables: true,
+            ..Default::default()
+        };
+        let err = liquid_template
+            .render(&palette, options)
+            .expect_err("Should not have been able to render an undefined variable in a partial");
+        assert!(err.to_string().ends_with(&format!(
+            "\n{}:2:5: undefined variable \"bg_9\" (did you mean \"bg_0\", \"bg_1\" or \"bg_2\"?)",
+            partial_path.display()
+        )));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::forloop("{% for i in (1..3) %}{{ forloop.index }}{% endfor %}", "123")]
+    #[case::cycle("{% for i in (1..3) %}{% cycle \"a\", \"b\" %}{% endfor %}", "aba")]
+    #[case::tablerow(
+        "{% tablerow i in (1..1) %}{{ tablerow.col }}{% endtablerow %}",
+        "<tr class=\"row1\"><td class=\"col1\">1</td></tr>"
+    )]
+    #[case::increment("{% increment n %}{% increment n %}{{ n }}", "012")]
+    #[case::decrement("{% decrement n %}{{ n }}", "-1-1")]
+    fn test_render_strict_variables_runtime_names(
+        tmpdir: TempDirFixture,
+        palette: Base16Palette,
+        #[case] liquid_template_content: &str,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let liquid_template = tmpdir.create_liquid_template_no_partials(liquid_template_content)?;
+
+        let options = RenderOptions {
+            strict_variables: true,
+            ..Default::default()
+        };
+        assert_eq!(liquid_template.render(&palette, options)?, expected);
+
+        Ok(())
+    }
 }
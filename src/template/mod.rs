@@ -1,11 +1,23 @@
 use crate::palette::Palette;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 /// Rendering options.
+#[derive(Clone, Default)]
 pub struct RenderOptions {
     /// Whether to unroll the colors in `palette` as sRGB hex strings.
     pub unroll_colors_hex: bool,
+
+    /// Extra variables to inject into the template (e.g. `font_family`),
+    /// alongside `palette` and the unrolled color names, which they must not
+    /// collide with.
+    #[cfg(feature = "liquid")]
+    pub variables: ::liquid::Object,
+
+    /// Whether to fail on any undefined variable, including where Liquid lets
+    /// it pass as `nil` (e.g. in `{% if %}` conditions), reporting where it is
+    /// referenced and the closest defined names.
+    pub strict_variables: bool,
 }
 
 /// A trait for an object that can render itself given an N-color palette.
@@ -83,7 +95,8 @@ pub struct RenderOptions {
 /// let palette = Base16Palette::from_yaml(&yaml).unwrap();
 /// let template = LiquidTemplate::parse_file(Path::new("selenized.md.liquid"), Vec::new()).unwrap();
 ///
-/// let rendered = template.render(&palette, RenderOptions { unroll_colors_hex: true }).unwrap();
+/// let options = RenderOptions { unroll_colors_hex: true, ..Default::default() };
+/// let rendered = template.render(&palette, options).unwrap();
 ///
 /// assert_eq!(rendered, r#"# Selenized Light
 ///
@@ -104,7 +117,46 @@ pub trait PaletteRenderer<const N: usize> {
     /// * `palette` - The given Palette object reference.
     /// * `options` - Rendering options for this template and its injected palette.
     fn render(&self, palette: &Palette<N>, options: RenderOptions) -> Result<String>;
+
+    /// Render this template (self), given several Palette object references at
+    /// once, e.g. for an output that carries both a light and a dark variant.
+    ///
+    /// By default, only a single palette is supported, rendered by `render`.
+    ///
+    /// * `palettes` - The given Palette object references, the first of which
+    ///   is the template's main palette (as in `render`).
+    /// * `options` - Rendering options for this template and its injected palettes.
+    fn render_palettes(&self, palettes: &[&Palette<N>], options: RenderOptions) -> Result<String> {
+        match palettes {
+            [palette] => self.render(palette, options),
+            _ => bail!(
+                "Could not render template: expected a single palette, found {}",
+                palettes.len()
+            ),
+        }
+    }
 }
 
+#[cfg(feature = "liquid")]
+mod filters;
+
+#[cfg(feature = "liquid")]
+mod strict;
+
+#[cfg(feature = "liquid")]
+mod partials;
+#[cfg(feature = "liquid")]
+pub use partials::{PartialConflict, PartialFiles, PartialOptions, PartialPrecedence, PartialsDir};
+
+#[cfg(feature = "liquid")]
+mod lint;
+#[cfg(feature = "liquid")]
+pub use lint::{LintReport, ReferenceKind, UnknownReference, UnreferencedColor};
+
+#[cfg(feature = "liquid")]
+mod front_matter;
+#[cfg(feature = "liquid")]
+pub use front_matter::FrontMatter;
+
 #[cfg(feature = "liquid")]
 pub mod liquid;
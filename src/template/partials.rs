@@ -0,0 +1,436 @@
+use anyhow::{anyhow, bail, Context, Result};
+use glob::glob;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{canonicalize, read_to_string};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Which of several same-named partials, from different partials directories,
+/// is used.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PartialPrecedence {
+    /// The partial from the first directory given.
+    First,
+    /// The partial from the last directory given.
+    #[default]
+    Last,
+}
+
+impl FromStr for PartialPrecedence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<PartialPrecedence> {
+        match s {
+            "first" => Ok(PartialPrecedence::First),
+            "last" => Ok(PartialPrecedence::Last),
+            _ => Err(anyhow!(
+                "Unknown partial precedence: \"{}\" (expected \"first\" or \"last\")",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for loading template partials from several directories.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PartialOptions {
+    /// Which of several same-named partials is used.
+    pub precedence: PartialPrecedence,
+
+    /// Whether same-named partials are an error, rather than a conflict
+    /// resolved by `precedence` (see [`PartialFiles::conflicts`]).
+    pub deny_conflicts: bool,
+}
+
+/// A directory to load template partials from, with the namespace its partials
+/// are also named in (see [`PartialFiles`]).
+///
+/// Parsed from its path, namespaced by the directory's name, or from
+/// `name=path` to namespace it explicitly.
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(try_from = "String")]
+pub struct PartialsDir {
+    /// The namespace given explicitly to the directory's partials, if any.
+    pub namespace: Option<String>,
+
+    /// The path to the directory.
+    pub path: PathBuf,
+}
+
+impl From<PathBuf> for PartialsDir {
+    fn from(path: PathBuf) -> PartialsDir {
+        PartialsDir {
+            namespace: None,
+            path,
+        }
+    }
+}
+
+impl FromStr for PartialsDir {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<PartialsDir> {
+        match s.split_once('=') {
+            Some((namespace, path)) if !namespace.contains(['/', '\\']) => {
+                if namespace.is_empty() || path.is_empty() {
+                    bail!(
+                        "Invalid partials directory: \"{}\" (expected \"name=path\")",
+                        s
+                    );
+                }
+                Ok(PartialsDir {
+                    namespace: Some(namespace.to_string()),
+                    path: PathBuf::from(path),
+                })
+            }
+            _ => Ok(PartialsDir::from(PathBuf::from(s))),
+        }
+    }
+}
+
+impl TryFrom<String> for PartialsDir {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<PartialsDir> {
+        s.parse()
+    }
+}
+
+/// Two partials, from different partials directories, with the same name.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PartialConflict {
+    /// The name of the partials, e.g. `header.liquid`.
+    pub name: String,
+
+    /// The path to the partial file used.
+    pub used: PathBuf,
+
+    /// The path to the partial file shadowed by it.
+    pub shadowed: PathBuf,
+}
+
+impl fmt::Display for PartialConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "partial \"{}\" in {} is shadowed by {}",
+            self.name,
+            self.shadowed.display(),
+            self.used.display()
+        )
+    }
+}
+
+/// The template partials (`.liquid` files) loaded from partials directories,
+/// for `{% render %}` or `{% include %}` directive tags.
+///
+/// Each partial is named by its file name (e.g. `header.liquid`), and also by
+/// its file name namespaced with its directory's name or explicit namespace
+/// (e.g. `common/header.liquid`), so that same-named partials from different
+/// directories can each be addressed.
+#[derive(Clone, Default, Debug)]
+pub struct PartialFiles {
+    /// The partials' file paths and sources, keyed by their names.
+    pub(crate) files: BTreeMap<String, (PathBuf, String)>,
+
+    /// The conflicts between same-named partials.
+    conflicts: Vec<PartialConflict>,
+
+    /// The canonical paths of the directories loaded, keyed by their namespaces.
+    namespaces: BTreeMap<String, PathBuf>,
+}
+
+impl PartialFiles {
+    /// Loads all `.liquid` files in the given directories, in order.
+    ///
+    /// Fails if two different directories have the same namespace, or on a
+    /// conflict between same-named partials if
+    /// [`PartialOptions::deny_conflicts`] is set; otherwise, the partial used
+    /// is chosen by [`PartialOptions::precedence`]. A directory given more than
+    /// once is only loaded once.
+    pub fn load(partials_dirs: &[PartialsDir], options: &PartialOptions) -> Result<PartialFiles> {
+        let mut partials = PartialFiles::default();
+        for dir in partials_dirs {
+            partials.load_dir(dir, options)?;
+        }
+
+        Ok(partials)
+    }
+
+    /// Returns the conflicts between same-named partials, resolved by
+    /// precedence, to warn about.
+    pub fn conflicts(&self) -> &[PartialConflict] {
+        &self.conflicts
+    }
+
+    /// Returns the names of the partials.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Loads all `.liquid` files in the given directory.
+    fn load_dir(&mut self, dir: &PartialsDir, options: &PartialOptions) -> Result<()> {
+        let dirpath = &dir.path;
+        let pattern = format!("{}/*.liquid", dirpath.to_str().unwrap());
+        let matching_paths = glob(&pattern)?;
+
+        let canonical_dirpath = canonicalize(dirpath).unwrap_or_else(|_| dirpath.to_path_buf());
+        let namespace = dir.namespace.clone().or_else(|| {
+            canonical_dirpath
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(String::from)
+        });
+        if let Some(namespace) = &namespace {
+            match self.namespaces.get(namespace) {
+                Some(existing) if *existing == canonical_dirpath => return Ok(()),
+                Some(existing) => bail!(
+                    "Partials directories {} and {} share the namespace \"{}\" (namespace them explicitly as \"name=path\")",
+                    existing.display(),
+                    canonical_dirpath.display(),
+                    namespace
+                ),
+                None => {
+                    self.namespaces
+                        .insert(namespace.clone(), canonical_dirpath.clone());
+                }
+            }
+        }
+
+        for path in matching_paths.filter_map(core::result::Result::ok) {
+            let basename = String::from(path.file_name().unwrap().to_str().unwrap());
+            let contents = read_to_string(&path)
+                .with_context(|| format!("Could not read partial file: {}", path.display()))?;
+
+            if let Some(namespace) = &namespace {
+                let name = format!("{}/{}", namespace, basename);
+                self.insert(name, &path, &contents, options)?;
+            }
+            self.insert(basename, &path, &contents, options)?;
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a partial, resolving a conflict with a same-named one by the
+    /// given options.
+    fn insert(
+        &mut self,
+        name: String,
+        path: &Path,
+        contents: &str,
+        options: &PartialOptions,
+    ) -> Result<()> {
+        let Some((existing, _)) = self.files.get(&name) else {
+            self.files
+                .insert(name, (path.to_path_buf(), contents.to_string()));
+            return Ok(());
+        };
+        // The same file, from a directory given twice under different names.
+        if let (Ok(existing), Ok(path)) = (canonicalize(existing), canonicalize(path)) {
+            if existing == path {
+                return Ok(());
+            }
+        }
+
+        let (used, shadowed) = match options.precedence {
+            PartialPrecedence::First => (existing.clone(), path.to_path_buf()),
+            PartialPrecedence::Last => (path.to_path_buf(), existing.clone()),
+        };
+        let conflict = PartialConflict {
+            name,
+            used,
+            shadowed,
+        };
+        if options.deny_conflicts {
+            bail!("Conflicting partials: {}", conflict);
+        }
+
+        if options.precedence == PartialPrecedence::Last {
+            self.files.insert(
+                conflict.name.clone(),
+                (path.to_path_buf(), contents.to_string()),
+            );
+        }
+        self.conflicts.push(conflict);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+    use std::fs::{create_dir, create_dir_all, write};
+    use tempdir::TempDir;
+
+    /// Creates two partials directories, `common` and `theme`, both with a
+    /// `header.liquid` partial.
+    #[fixture]
+    fn dirs() -> (TempDir, Vec<PartialsDir>) {
+        let tmpdir = TempDir::new("partials").unwrap();
+        let dirs = vec![tmpdir.path().join("common"), tmpdir.path().join("theme")];
+        for dir in &dirs {
+            create_dir(dir).unwrap();
+            let name = dir.file_name().unwrap().to_str().unwrap();
+            write(dir.join("header.liquid"), name).unwrap();
+        }
+        write(dirs[0].join("footer.liquid"), "footer").unwrap();
+
+        (tmpdir, dirs.into_iter().map(PartialsDir::from).collect())
+    }
+
+    /// Creates two partials directories with the same name, `a/partials` and
+    /// `b/partials`, both with a `header.liquid` partial.
+    #[fixture]
+    fn same_named_dirs() -> (TempDir, Vec<PathBuf>) {
+        let tmpdir = TempDir::new("partials").unwrap();
+        let dirs = vec![
+            tmpdir.path().join("a").join("partials"),
+            tmpdir.path().join("b").join("partials"),
+        ];
+        for dir in &dirs {
+            create_dir_all(dir).unwrap();
+            let name = dir.parent().unwrap().file_name().unwrap().to_str().unwrap();
+            write(dir.join("header.liquid"), name).unwrap();
+        }
+
+        (tmpdir, dirs)
+    }
+
+    fn source<'a>(partials: &'a PartialFiles, name: &str) -> &'a str {
+        &partials.files[name].1
+    }
+
+    #[rstest]
+    #[case::first(PartialPrecedence::First, "common", "theme")]
+    #[case::last(PartialPrecedence::Last, "theme", "common")]
+    fn test_load_precedence(
+        dirs: (TempDir, Vec<PartialsDir>),
+        #[case] precedence: PartialPrecedence,
+        #[case] used: &str,
+        #[case] shadowed: &str,
+    ) -> Result<()> {
+        let (_tmpdir, dirs) = dirs;
+        let options = PartialOptions {
+            precedence,
+            ..Default::default()
+        };
+
+        let partials = PartialFiles::load(&dirs, &options)?;
+        assert_eq!(
+            partials.names().collect::<Vec<_>>(),
+            vec![
+                "common/footer.liquid",
+                "common/header.liquid",
+                "footer.liquid",
+                "header.liquid",
+                "theme/header.liquid",
+            ]
+        );
+        assert_eq!(source(&partials, "header.liquid"), used);
+        assert_eq!(source(&partials, "common/header.liquid"), "common");
+        assert_eq!(source(&partials, "theme/header.liquid"), "theme");
+
+        let conflict = &partials.conflicts()[0];
+        assert_eq!(partials.conflicts().len(), 1);
+        assert_eq!(conflict.name, "header.liquid");
+        assert!(conflict.used.ends_with(format!("{}/header.liquid", used)));
+        assert!(conflict
+            .shadowed
+            .ends_with(format!("{}/header.liquid", shadowed)));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_deny_conflicts(dirs: (TempDir, Vec<PartialsDir>)) {
+        let (_tmpdir, dirs) = dirs;
+        let options = PartialOptions {
+            deny_conflicts: true,
+            ..Default::default()
+        };
+
+        let err = PartialFiles::load(&dirs, &options).unwrap_err();
+        assert!(err.to_string().contains("partial \"header.liquid\""));
+        assert!(PartialFiles::load(&dirs[..1], &options).is_ok());
+    }
+
+    #[rstest]
+    fn test_load_same_named_dirs(same_named_dirs: (TempDir, Vec<PathBuf>)) -> Result<()> {
+        let (_tmpdir, dirs) = same_named_dirs;
+        let options = PartialOptions::default();
+
+        let unnamed: Vec<PartialsDir> = dirs.iter().cloned().map(PartialsDir::from).collect();
+        let err = PartialFiles::load(&unnamed, &options).unwrap_err();
+        assert!(err.to_string().contains("share the namespace \"partials\""));
+
+        let named = vec![
+            format!("a={}", dirs[0].display()).parse()?,
+            format!("b={}", dirs[1].display()).parse()?,
+        ];
+        let partials = PartialFiles::load(&named, &options)?;
+        assert_eq!(source(&partials, "a/header.liquid"), "a");
+        assert_eq!(source(&partials, "b/header.liquid"), "b");
+        assert_eq!(source(&partials, "header.liquid"), "b");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_load_same_dir_twice(dirs: (TempDir, Vec<PartialsDir>)) -> Result<()> {
+        let (_tmpdir, dirs) = dirs;
+        let options = PartialOptions {
+            deny_conflicts: true,
+            ..Default::default()
+        };
+        let common = &dirs[0].path;
+        let twice = vec![
+            dirs[0].clone(),
+            PartialsDir::from(common.join("..").join("common")),
+            format!("shared={}", common.display()).parse()?,
+        ];
+
+        let partials = PartialFiles::load(&twice, &options)?;
+        assert!(partials.conflicts().is_empty());
+        assert_eq!(source(&partials, "common/header.liquid"), "common");
+        assert_eq!(source(&partials, "shared/header.liquid"), "common");
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::path("common", None, "common")]
+    #[case::named("theme=vendor/partials", Some("theme"), "vendor/partials")]
+    #[case::path_with_equals("vendor/a=b", None, "vendor/a=b")]
+    fn test_partials_dir_from_str(
+        #[case] s: &str,
+        #[case] namespace: Option<&str>,
+        #[case] path: &str,
+    ) -> Result<()> {
+        let dir: PartialsDir = s.parse()?;
+        assert_eq!(dir.namespace.as_deref(), namespace);
+        assert_eq!(dir.path, PathBuf::from(path));
+        assert!("=common".parse::<PartialsDir>().is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_precedence_from_str() {
+        assert_eq!(
+            "first".parse::<PartialPrecedence>().unwrap(),
+            PartialPrecedence::First
+        );
+        assert_eq!(
+            "last".parse::<PartialPrecedence>().unwrap(),
+            PartialPrecedence::Last
+        );
+        assert!("middle".parse::<PartialPrecedence>().is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use liquid::model::{DisplayCow, KStringCow, ObjectView, State, Value, ValueView};
+use liquid::Object;
+use std::cell::RefCell;
+use std::fmt;
+
+use crate::palette::Palette;
+
+/// The variables Liquid defines at runtime (in `{% for %}` and `{% tablerow %}`
+/// loops, also looked up by `{% cycle %}`), which it may still look up in a
+/// template's globals.
+pub(crate) const RUNTIME_NAMES: [&str; 2] = ["forloop", "tablerow"];
+
+/// A template's globals which record the names of the variables looked up in
+/// them but undefined, including those that Liquid lets pass as `nil` (e.g. in
+/// `{% if %}` conditions).
+pub(crate) struct StrictGlobals<'a> {
+    globals: &'a Object,
+
+    /// The template's `{% increment %}` and `{% decrement %}` counters, which
+    /// Liquid looks up in the globals before its own.
+    counters: Vec<String>,
+
+    undefined: RefCell<Vec<String>>,
+}
+
+impl<'a> StrictGlobals<'a> {
+    pub(crate) fn new(globals: &'a Object, counters: Vec<String>) -> Self {
+        Self {
+            globals,
+            counters,
+            undefined: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the names of the undefined variables looked up, in order of
+    /// first lookup.
+    pub(crate) fn undefined(self) -> Vec<String> {
+        self.undefined.into_inner()
+    }
+
+    fn record(&self, index: &str) {
+        if RUNTIME_NAMES.contains(&index) || self.counters.iter().any(|name| name == index) {
+            return;
+        }
+        let mut undefined = self.undefined.borrow_mut();
+        if !undefined.iter().any(|name| name == index) {
+            undefined.push(index.to_string());
+        }
+    }
+}
+
+impl fmt::Debug for StrictGlobals<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.globals.fmt(f)
+    }
+}
+
+impl ValueView for StrictGlobals<'_> {
+    fn as_debug(&self) -> &dyn fmt::Debug {
+        self
+    }
+
+    fn render(&self) -> DisplayCow<'_> {
+        self.globals.render()
+    }
+
+    fn source(&self) -> DisplayCow<'_> {
+        self.globals.source()
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.globals.type_name()
+    }
+
+    fn query_state(&self, state: State) -> bool {
+        self.globals.query_state(state)
+    }
+
+    fn to_kstr(&self) -> KStringCow<'_> {
+        self.globals.to_kstr()
+    }
+
+    fn to_value(&self) -> Value {
+        self.globals.to_value()
+    }
+
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        Some(self)
+    }
+}
+
+impl ObjectView for StrictGlobals<'_> {
+    fn as_value(&self) -> &dyn ValueView {
+        self
+    }
+
+    fn size(&self) -> i64 {
+        self.globals.size()
+    }
+
+    fn keys<'k>(&'k self) -> Box<dyn Iterator<Item = KStringCow<'k>> + 'k> {
+        ObjectView::keys(self.globals)
+    }
+
+    fn values<'k>(&'k self) -> Box<dyn Iterator<Item = &'k dyn ValueView> + 'k> {
+        ObjectView::values(self.globals)
+    }
+
+    fn iter<'k>(&'k self) -> Box<dyn Iterator<Item = (KStringCow<'k>, &'k dyn ValueView)> + 'k> {
+        ObjectView::iter(self.globals)
+    }
+
+    fn contains_key(&self, index: &str) -> bool {
+        let found = self.globals.contains_key(index);
+        if !found {
+            self.record(index);
+        }
+        found
+    }
+
+    fn get<'s>(&'s self, index: &str) -> Option<&'s dyn ValueView> {
+        let value = ObjectView::get(self.globals, index);
+        if value.is_none() {
+            self.record(index);
+        }
+        value
+    }
+}
+
+/// Describes how to fix a reference to an undefined variable: a reminder that
+/// color names are only variables when unrolled, or the closest defined names.
+pub(crate) fn undefined_hint<const N: usize>(
+    name: &str,
+    defined: &[&str],
+    palette: &Palette<N>,
+) -> String {
+    if palette.colors.iter().any(|color| color.name == name) {
+        " (palette color names are only variables when unrolling colors as hex)".to_string()
+    } else {
+        did_you_mean(name, defined)
+    }
+}
+
+/// Describes the candidates closest to `name` (see [`suggest`]) as a " (did you
+/// mean ...?)" suffix, or nothing if there are none.
+pub(crate) fn did_you_mean(name: &str, candidates: &[&str]) -> String {
+    match suggest(name, candidates).split_last() {
+        None => String::new(),
+        Some((last, [])) => format!(" (did you mean \"{}\"?)", last),
+        Some((last, others)) => format!(
+            " (did you mean \"{}\" or \"{}\"?)",
+            others.join("\", \""),
+            last
+        ),
+    }
+}
+
+/// Returns the (up to 3) candidates closest to `name` by edit distance, if
+/// close enough to be likely typos of it.
+pub(crate) fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let mut close: Vec<(usize, &str)> = candidates
+        .iter()
+        .map(|candidate| (levenshtein(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    close.sort_by_key(|(distance, _)| *distance);
+    close.dedup_by_key(|(_, candidate)| *candidate);
+
+    close
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// The Levenshtein edit distance between two strings, by characters.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    #[rstest]
+    fn test_suggest() {
+        let candidates = ["palette", "bg_0", "bg_1", "fg_0", "blue"];
+        assert_eq!(suggest("bg_3", &candidates), vec!["bg_0", "bg_1", "fg_0"]);
+        assert_eq!(suggest("palete", &candidates), vec!["palette"]);
+        assert!(suggest("magenta", &candidates).is_empty());
+    }
+
+    #[rstest]
+    fn test_strict_globals() {
+        let globals = liquid::object!({ "bg_0": "fef3da" });
+        let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+        let template = parser
+            .parse("{% if bg_3 %}{% endif %}{{ bg_0 }}{% assign x = 1 %}{{ x }}")
+            .unwrap();
+
+        let strict_globals = StrictGlobals::new(&globals, Vec::new());
+        assert_eq!(template.render(&strict_globals).unwrap(), "fef3da1");
+        assert_eq!(strict_globals.undefined(), vec!["bg_3"]);
+    }
+
+    #[rstest]
+    fn test_strict_globals_runtime_names() {
+        let globals = liquid::object!({});
+        let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+        let template = parser
+            .parse(concat!(
+                "{% for i in (1..2) %}{% cycle \"a\", \"b\" %}{{ forloop.index }}{% endfor %}",
+                "{% tablerow i in (1..1) %}{{ tablerow.col }}{% endtablerow %}",
+                "{% increment n %}{{ n }}{% decrement m %}"
+            ))
+            .unwrap();
+
+        let strict_globals = StrictGlobals::new(&globals, vec!["n".to_string(), "m".to_string()]);
+        template.render(&strict_globals).unwrap();
+        assert!(strict_globals.undefined().is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+//! Fixtures shared by the crate's unit tests.
+
+use anyhow::Result;
+use rstest::*;
+use std::fs;
+use std::path::PathBuf;
+use tempdir::TempDir;
+
+pub(crate) struct TempDirFixture {
+    pub(crate) tmpdir: TempDir,
+}
+
+impl TempDirFixture {
+    /// Writes the given UTF-8 contents string into a file in this TempDir fixture.
+    ///
+    /// Returns a full filepath to the newly created file.
+    pub(crate) fn write_to_file(&self, filename: &str, contents: &str) -> Result<PathBuf> {
+        let filepath = self.tmpdir.path().join(filename);
+        fs::write(filepath.clone(), contents)?;
+
+        Ok(filepath)
+    }
+}
+
+#[fixture]
+pub(crate) fn tmpdir() -> TempDirFixture {
+    TempDirFixture {
+        tmpdir: TempDir::new("tests").unwrap(),
+    }
+}
+
+#[fixture]
+pub(crate) fn tmpdir_2() -> TempDirFixture {
+    TempDirFixture {
+        tmpdir: TempDir::new("tests_2").unwrap(),
+    }
+}
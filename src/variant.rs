@@ -0,0 +1,207 @@
+use palette::white_point::D65;
+use palette::{lab::Lab, FromColor, Xyz};
+use serde::{Deserialize, Serialize};
+
+use crate::generate::in_gamut;
+use crate::palette::{contrast_ratio, relative_luminance, Base16Palette, Palette};
+
+/// A palette's polarity: whether it is a light or a dark variant.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Polarity {
+    /// A dark foreground on a light background.
+    Light,
+    /// A light foreground on a dark background.
+    Dark,
+}
+
+impl<const N: usize> Palette<N> {
+    /// Returns this palette's polarity if it is a Base16 palette (see
+    /// [`Palette::polarity`]), or `None` for palettes of any other size.
+    pub fn base16_polarity(&self) -> Option<Polarity> {
+        if N != 16 {
+            return None;
+        }
+
+        Some(polarity_of(&self.colors[0].lab, &self.colors[5].lab))
+    }
+}
+
+impl Palette<16> {
+    /// Returns this palette's polarity, by whether its background (base00) is
+    /// lighter than its default foreground (base05).
+    pub fn polarity(&self) -> Polarity {
+        polarity_of(&self.colors[0].lab, &self.colors[5].lab)
+    }
+
+    /// Derives this palette's opposite-polarity variant (i.e. dark from light,
+    /// or light from dark), with the given name.
+    ///
+    /// The monotone ramp [base00..base07] is reversed: each slot keeps its
+    /// color name, but takes the L*a*b* values of its mirrored slot (base00
+    /// those of base07, and so on).
+    ///
+    /// Accents [base08..base0f] keep their hues and chroma, but their lightness
+    /// is moved to the other side of the new background, so that their WCAG
+    /// contrast ratio against it matches the one they had against the old
+    /// background, as far as the sRGB gamut allows. Accents that would fall out
+    /// of gamut lose some chroma.
+    pub fn opposite_variant<S>(&self, name: S) -> Base16Palette
+    where
+        S: Into<String>,
+    {
+        let mut variant = self.clone();
+        variant.name = name.into();
+
+        for (index, color) in variant.colors[..8].iter_mut().enumerate() {
+            color.lab = self.colors[7 - index].lab;
+        }
+
+        let old_bg = self.colors[0].lab;
+        let new_bg = variant.colors[0].lab;
+        for color in &mut variant.colors[8..] {
+            color.lab = mirror_accent(&color.lab, &old_bg, &new_bg);
+        }
+
+        variant
+    }
+}
+
+/// Returns the polarity of a palette with the given background and foreground.
+fn polarity_of(background: &Lab, foreground: &Lab) -> Polarity {
+    if background.l > foreground.l {
+        Polarity::Light
+    } else {
+        Polarity::Dark
+    }
+}
+
+/// Moves an accent to the other side of `new_bg` in lightness, at the same
+/// contrast ratio it had against `old_bg`.
+fn mirror_accent(accent: &Lab, old_bg: &Lab, new_bg: &Lab) -> Lab {
+    let ratio = contrast_ratio(accent, old_bg);
+    let bg_luminance = relative_luminance(new_bg);
+
+    // Accents darker than the old background become lighter than the new one,
+    // and vice versa.
+    let luminance = if accent.l < old_bg.l {
+        ratio * (bg_luminance + 0.05) - 0.05
+    } else {
+        (bg_luminance + 0.05) / ratio - 0.05
+    };
+    let l = Lab::from_color(Xyz::<D65>::new(0.0, luminance.clamp(0.0, 1.0), 0.0)).l;
+
+    // Keep the hue, and as much of the chroma as the sRGB gamut allows.
+    let with_chroma = |scale: f32| Lab::new(l, accent.a * scale, accent.b * scale);
+    if in_gamut(&with_chroma(1.0)) {
+        return with_chroma(1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    while high - low > 1e-3 {
+        let mid = (low + high) / 2.0;
+        if in_gamut(&with_chroma(mid)) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    with_chroma(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::BaseColor;
+
+    use rstest::*;
+
+    #[fixture]
+    fn palette() -> Base16Palette {
+        Palette::new(
+            "Selenized light",
+            [
+                // in Base16 framework:
+                BaseColor::new("bg_0", 96, 0, 13), // base00 - default background
+                BaseColor::new("bg_1", 91, 0, 13), // base01 - darker bg
+                BaseColor::new("bg_2", 82, 0, 13), // base02 - selection bg
+                BaseColor::new("dim_0", 62, -4, 1), // base03 - comments, invis
+                BaseColor::new("fg_0", 42, -6, -6), // base04 - light foreground
+                BaseColor::new("fg_1", 31, -6, -6), // base05 - default foreground
+                BaseColor::new("unused_0", 28, -13, -13), // base06 - dark fg - unused
+                BaseColor::new("unused_1", 23, -12, -12), // base07 - dark bg - unused
+                BaseColor::new("red", 46, 66, 42), // base08 - vars, diff deleted
+                BaseColor::new("orange", 52, 39, 52), // base09 - ints, bools, consts
+                BaseColor::new("magenta", 52, 58, -16), // base0a - classes, search bg
+                BaseColor::new("green", 54, -40, 58), // base0b - strings, diff inserted
+                BaseColor::new("cyan", 57, -42, -4), // base0c - regex, escape chars
+                BaseColor::new("blue", 46, 0, -60), // base0d - funcs, headings
+                BaseColor::new("yellow", 59, 6, 71), // base0e - keywords, diff changed
+                BaseColor::new("violet", 49, 32, -47), // base0f - deprecated, embeds
+            ],
+        )
+    }
+
+    #[rstest]
+    fn test_base16_polarity(palette: Base16Palette) {
+        assert_eq!(palette.base16_polarity(), Some(Polarity::Light));
+
+        let two_colors = Palette::new(
+            "Two colors",
+            [
+                BaseColor::new("bg", 96, 0, 13),
+                BaseColor::new("fg", 31, -6, -6),
+            ],
+        );
+        assert_eq!(two_colors.base16_polarity(), None);
+    }
+
+    #[rstest]
+    fn test_opposite_variant_monotones(palette: Base16Palette) {
+        let dark = palette.opposite_variant("Selenized dark");
+        assert_eq!(dark.name, "Selenized dark");
+        assert_eq!(palette.polarity(), Polarity::Light);
+        assert_eq!(dark.polarity(), Polarity::Dark);
+
+        for index in 0..8 {
+            assert_eq!(dark.colors[index].name, palette.colors[index].name);
+            assert_eq!(dark.colors[index].lab, palette.colors[7 - index].lab);
+        }
+    }
+
+    #[rstest]
+    fn test_opposite_variant_accents(palette: Base16Palette) {
+        let dark = palette.opposite_variant("Selenized dark");
+
+        for (light_accent, dark_accent) in palette.colors[8..].iter().zip(&dark.colors[8..]) {
+            assert_eq!(dark_accent.name, light_accent.name);
+            assert!(dark_accent.lab.l > dark.colors[0].lab.l);
+            assert!(in_gamut(&dark_accent.lab));
+
+            let light_contrast = light_accent.contrast_ratio(&palette.colors[0]);
+            let dark_contrast = dark_accent.contrast_ratio(&dark.colors[0]);
+            assert!(
+                (light_contrast - dark_contrast).abs() < 0.05,
+                "{}: {} != {}",
+                light_accent.name,
+                light_contrast,
+                dark_contrast
+            );
+        }
+    }
+
+    #[rstest]
+    fn test_opposite_variant_round_trip(palette: Base16Palette) {
+        let light = palette
+            .opposite_variant("Selenized dark")
+            .opposite_variant("Selenized light");
+
+        assert_eq!(light.colors[..8], palette.colors[..8]);
+        for (accent, round_tripped) in palette.colors[8..].iter().zip(&light.colors[8..]) {
+            let contrast = accent.contrast_ratio(&palette.colors[0]);
+            let round_tripped_contrast = round_tripped.contrast_ratio(&light.colors[0]);
+            assert!((contrast - round_tripped_contrast).abs() < 0.05);
+        }
+    }
+}